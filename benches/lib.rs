@@ -46,4 +46,14 @@ mod to_html {
 
         b.iter(|| render_html(&buf, Options::empty()));
     }
+
+    #[bench]
+    fn literal_angle_brackets(b: &mut test::Bencher) {
+        let mut buf = String::new();
+        for i in 0..1000 {
+            buf.push_str(&format!("a < b << c <= {} and x<y\n", i));
+        }
+
+        b.iter(|| render_html(&buf, Options::empty()));
+    }
 }
@@ -216,6 +216,11 @@ fn regression_test() -> i32 {
     check_pattern("a***".into());
     check_pattern("[[]()".into());
     check_pattern("[a](<".into());
+    check_pattern(Pattern {
+        prefix: "".into(),
+        repeating_pattern: "> [!\n\n".into(),
+        suffix: "]".into(),
+    });
     exit_code
 }
 
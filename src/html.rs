@@ -26,7 +26,7 @@ use std::io::{self, ErrorKind, Write};
 
 use crate::escape::{escape_href, escape_html};
 use crate::parse::Event::*;
-use crate::parse::{Alignment, CodeBlockKind, Event, LinkType, Tag};
+use crate::parse::{AdmonitionKind, Alignment, CodeBlockKind, Event, LinkType, Tag};
 use crate::strings::CowStr;
 
 enum TableState {
@@ -106,6 +106,9 @@ struct HtmlWriter<'a, I, W> {
     table_alignments: Vec<Alignment>,
     table_cell_index: usize,
     numbers: HashMap<CowStr<'a>, usize>,
+    /// Heading ids used so far, with the number of duplicates of each.
+    heading_ids: HashMap<String, usize>,
+    renderer: HtmlRenderer,
 }
 
 impl<'a, I, W> HtmlWriter<'a, I, W>
@@ -113,7 +116,7 @@ where
     I: Iterator<Item = Event<'a>>,
     W: StrWrite,
 {
-    fn new(iter: I, writer: W) -> Self {
+    fn new(iter: I, writer: W, renderer: HtmlRenderer) -> Self {
         Self {
             iter,
             writer,
@@ -122,6 +125,8 @@ where
             table_alignments: vec![],
             table_cell_index: 0,
             numbers: HashMap::new(),
+            heading_ids: HashMap::new(),
+            renderer,
         }
     }
 
@@ -145,53 +150,131 @@ where
     pub fn run(mut self) -> io::Result<()> {
         while let Some(event) = self.iter.next() {
             match event {
-                Start(tag) => {
-                    self.start_tag(tag)?;
+                Start(Tag::Heading(level)) if self.renderer.heading_ids => {
+                    self.heading_with_id(level)?;
                 }
-                End(tag) => {
-                    self.end_tag(tag)?;
-                }
-                Text(text) => {
-                    escape_html(&mut self.writer, &text)?;
-                    self.end_newline = text.ends_with('\n');
-                }
-                Code(text) => {
-                    self.write("<code>")?;
-                    escape_html(&mut self.writer, &text)?;
-                    self.write("</code>")?;
+                event => self.write_event(event)?,
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes a heading with an id derived from its text. The id has to be known
+    /// before the start tag is written, so the heading's events are buffered.
+    fn heading_with_id(&mut self, level: u32) -> io::Result<()> {
+        let mut events = Vec::new();
+        let mut text = String::new();
+        for event in &mut self.iter {
+            match event {
+                Text(ref t) | Code(ref t) => text.push_str(t),
+                End(Tag::Heading(_)) => {
+                    events.push(event);
+                    break;
                 }
-                Html(html) => {
-                    self.write(&html)?;
+                _ => (),
+            }
+            events.push(event);
+        }
+
+        let id = self.unique_heading_id(github_slug(&text));
+        if !self.end_newline {
+            self.write_newline()?;
+        }
+        write!(&mut self.writer, "<h{} id=\"", level)?;
+        escape_html(&mut self.writer, &id)?;
+        self.write("\">")?;
+        self.end_newline = false;
+        for event in events {
+            self.write_event(event)?;
+        }
+        Ok(())
+    }
+
+    /// Deduplicates a heading id the way GitHub does, by appending `-1`, `-2`
+    /// and so on to repeated ids.
+    fn unique_heading_id(&mut self, base: String) -> String {
+        let mut id = base.clone();
+        if self.heading_ids.contains_key(&base) {
+            loop {
+                let count = self.heading_ids.get_mut(&base).unwrap();
+                *count += 1;
+                id = format!("{}-{}", base, count);
+                if !self.heading_ids.contains_key(&id) {
+                    break;
                 }
-                SoftBreak => {
+            }
+        }
+        self.heading_ids.insert(id.clone(), 0);
+        id
+    }
+
+    fn write_event(&mut self, event: Event<'a>) -> io::Result<()> {
+        match event {
+            Start(tag) => {
+                self.start_tag(tag)?;
+            }
+            End(tag) => {
+                self.end_tag(tag)?;
+            }
+            Text(text) => {
+                escape_html(&mut self.writer, &text)?;
+                self.end_newline = text.ends_with('\n');
+            }
+            Code(text) => {
+                self.write("<code>")?;
+                escape_html(&mut self.writer, &text)?;
+                self.write("</code>")?;
+            }
+            Html(html) => {
+                self.write(&html)?;
+            }
+            SoftBreak => {
+                if self.renderer.soft_break_as_space {
+                    self.write(" ")?;
+                } else {
                     self.write_newline()?;
                 }
-                HardBreak => {
-                    self.write("<br />\n")?;
-                }
-                Rule => {
-                    if self.end_newline {
-                        self.write("<hr />\n")?;
-                    } else {
-                        self.write("\n<hr />\n")?;
-                    }
-                }
-                FootnoteReference(name) => {
-                    let len = self.numbers.len() + 1;
-                    self.write("<sup class=\"footnote-reference\"><a href=\"#")?;
-                    escape_html(&mut self.writer, &name)?;
-                    self.write("\">")?;
-                    let number = *self.numbers.entry(name).or_insert(len);
-                    write!(&mut self.writer, "{}", number)?;
-                    self.write("</a></sup>")?;
-                }
-                TaskListMarker(true) => {
-                    self.write("<input disabled=\"\" type=\"checkbox\" checked=\"\"/>\n")?;
+            }
+            HardBreak(_) => {
+                self.write("<br />\n")?;
+            }
+            Rule => {
+                if self.end_newline {
+                    self.write("<hr />\n")?;
+                } else {
+                    self.write("\n<hr />\n")?;
                 }
-                TaskListMarker(false) => {
-                    self.write("<input disabled=\"\" type=\"checkbox\"/>\n")?;
+            }
+            FootnoteReference(name) => {
+                let len = self.numbers.len() + 1;
+                self.write("<sup class=\"footnote-reference\"><a href=\"#")?;
+                escape_html(&mut self.writer, &name)?;
+                self.write("\">")?;
+                let number = *self.numbers.entry(name).or_insert(len);
+                write!(&mut self.writer, "{}", number)?;
+                self.write("</a></sup>")?;
+            }
+            TaskListMarker(true) => {
+                self.write("<input disabled=\"\" type=\"checkbox\" checked=\"\"/>\n")?;
+            }
+            TaskListMarker(false) => {
+                self.write("<input disabled=\"\" type=\"checkbox\"/>\n")?;
+            }
+            InlineMath(text) => {
+                self.write("<span class=\"math math-inline\">")?;
+                escape_html(&mut self.writer, &text)?;
+                self.write("</span>")?;
+            }
+            DisplayMath(text) => {
+                if self.end_newline {
+                    self.write("<div class=\"math math-display\">")?;
+                } else {
+                    self.write("\n<div class=\"math math-display\">")?;
                 }
+                escape_html(&mut self.writer, &text)?;
+                self.write("</div>\n")?;
             }
+            ReferenceDefinition(_) | FrontMatter(_) => (),
         }
         Ok(())
     }
@@ -250,32 +333,46 @@ where
                     self.write("\n<blockquote>\n")
                 }
             }
+            Tag::Admonition(kind) => {
+                if !self.end_newline {
+                    self.write_newline()?;
+                }
+                let class = match kind {
+                    AdmonitionKind::Note => "note",
+                    AdmonitionKind::Tip => "tip",
+                    AdmonitionKind::Important => "important",
+                    AdmonitionKind::Warning => "warning",
+                    AdmonitionKind::Caution => "caution",
+                };
+                self.write("<blockquote class=\"markdown-alert-")?;
+                self.write(class)?;
+                self.write("\">\n")
+            }
             Tag::CodeBlock(info) => {
                 if !self.end_newline {
                     self.write_newline()?;
                 }
                 match info {
-                    CodeBlockKind::Fenced(info) => {
-                        let lang = info.split(' ').next().unwrap();
+                    CodeBlockKind::Fenced(lang, _) => {
                         if lang.is_empty() {
                             self.write("<pre><code>")
                         } else {
                             self.write("<pre><code class=\"language-")?;
-                            escape_html(&mut self.writer, lang)?;
+                            escape_html(&mut self.writer, &lang)?;
                             self.write("\">")
                         }
                     }
                     CodeBlockKind::Indented => self.write("<pre><code>"),
                 }
             }
-            Tag::List(Some(1)) => {
+            Tag::List(Some(1), _) => {
                 if self.end_newline {
                     self.write("<ol>\n")
                 } else {
                     self.write("\n<ol>\n")
                 }
             }
-            Tag::List(Some(start)) => {
+            Tag::List(Some(start), _) => {
                 if self.end_newline {
                     self.write("<ol start=\"")?;
                 } else {
@@ -284,7 +381,7 @@ where
                 write!(&mut self.writer, "{}", start)?;
                 self.write("\">\n")
             }
-            Tag::List(None) => {
+            Tag::List(None, _) => {
                 if self.end_newline {
                     self.write("<ul>\n")
                 } else {
@@ -301,6 +398,10 @@ where
             Tag::Emphasis => self.write("<em>"),
             Tag::Strong => self.write("<strong>"),
             Tag::Strikethrough => self.write("<del>"),
+            Tag::Superscript => self.write("<sup>"),
+            Tag::Subscript => self.write("<sub>"),
+            Tag::Spoiler => self.write("<span class=\"spoiler\">"),
+            Tag::Highlight => self.write("<mark>"),
             Tag::Link(LinkType::Email, dest, title) => {
                 self.write("<a href=\"mailto:")?;
                 escape_href(&mut self.writer, &dest)?;
@@ -377,16 +478,16 @@ where
                 }
                 self.table_cell_index += 1;
             }
-            Tag::BlockQuote => {
+            Tag::BlockQuote | Tag::Admonition(_) => {
                 self.write("</blockquote>\n")?;
             }
             Tag::CodeBlock(_) => {
                 self.write("</code></pre>\n")?;
             }
-            Tag::List(Some(_)) => {
+            Tag::List(Some(_), _) => {
                 self.write("</ol>\n")?;
             }
-            Tag::List(None) => {
+            Tag::List(None, _) => {
                 self.write("</ul>\n")?;
             }
             Tag::Item => {
@@ -401,6 +502,18 @@ where
             Tag::Strikethrough => {
                 self.write("</del>")?;
             }
+            Tag::Superscript => {
+                self.write("</sup>")?;
+            }
+            Tag::Subscript => {
+                self.write("</sub>")?;
+            }
+            Tag::Spoiler => {
+                self.write("</span>")?;
+            }
+            Tag::Highlight => {
+                self.write("</mark>")?;
+            }
             Tag::Link(_, _, _) => {
                 self.write("</a>")?;
             }
@@ -424,11 +537,11 @@ where
                     }
                     nest -= 1;
                 }
-                Html(text) | Code(text) | Text(text) => {
+                Html(text) | Code(text) | Text(text) | InlineMath(text) | DisplayMath(text) => {
                     escape_html(&mut self.writer, &text)?;
                     self.end_newline = text.ends_with('\n');
                 }
-                SoftBreak | HardBreak | Rule => {
+                SoftBreak | HardBreak(_) | Rule => {
                     self.write(" ")?;
                 }
                 FootnoteReference(name) => {
@@ -438,6 +551,7 @@ where
                 }
                 TaskListMarker(true) => self.write("[x]")?,
                 TaskListMarker(false) => self.write("[ ]")?,
+                ReferenceDefinition(_) | FrontMatter(_) => (),
             }
         }
         Ok(())
@@ -475,12 +589,16 @@ pub fn push_html<'a, I>(s: &mut String, iter: I)
 where
     I: Iterator<Item = Event<'a>>,
 {
-    HtmlWriter::new(iter, s).run().unwrap();
+    HtmlRenderer::new().push_html(s, iter);
 }
 
 /// Iterate over an `Iterator` of `Event`s, generate HTML for each `Event`, and
 /// write it out to a writable stream.
 ///
+/// Output is written as soon as each event is received. Apart from the alignments
+/// of the current table and the numbers assigned to footnotes, no state is kept
+/// between events, so documents of any size can be streamed.
+///
 /// **Note**: using this function with an unbuffered writer like a file or socket
 /// will result in poor performance. Wrap these in a
 /// [`BufWriter`](https://doc.rust-lang.org/std/io/struct.BufWriter.html) to
@@ -516,5 +634,90 @@ where
     I: Iterator<Item = Event<'a>>,
     W: Write,
 {
-    HtmlWriter::new(iter, WriteWrapper(writer)).run()
+    HtmlRenderer::new().write_html(writer, iter)
+}
+
+/// HTML renderer with configurable output. The [`push_html`](fn.push_html.html)
+/// and [`write_html`](fn.write_html.html) functions use the default
+/// configuration.
+///
+/// # Examples
+///
+/// ```
+/// use pulldown_cmark::{html::HtmlRenderer, Parser};
+///
+/// let mut html_buf = String::new();
+/// HtmlRenderer::new()
+///     .soft_break_as_space(true)
+///     .push_html(&mut html_buf, Parser::new("hello\nworld"));
+///
+/// assert_eq!(html_buf, "<p>hello world</p>\n");
+/// ```
+#[derive(Clone, Copy, Debug, Default)]
+pub struct HtmlRenderer {
+    soft_break_as_space: bool,
+    heading_ids: bool,
+}
+
+impl HtmlRenderer {
+    /// Creates a renderer with the default configuration.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Renders soft line breaks as a space instead of a newline. Useful when
+    /// the output has to fit on a single line.
+    pub fn soft_break_as_space(mut self, enable: bool) -> Self {
+        self.soft_break_as_space = enable;
+        self
+    }
+
+    /// Gives headings an `id` attribute derived from their text, like GitHub
+    /// does: lowercased, without punctuation and with spaces replaced by `-`.
+    /// Repeated ids get a `-1`, `-2`, etc. suffix in document order.
+    ///
+    /// The opening tag can only be written once the heading's text is known, so
+    /// the events of each heading are buffered until it ends. Every id handed
+    /// out is also remembered to detect repeats, which takes memory that grows
+    /// with the number of headings in the document.
+    pub fn heading_ids(mut self, enable: bool) -> Self {
+        self.heading_ids = enable;
+        self
+    }
+
+    /// Renders the events to HTML and pushes it to a `String`. See
+    /// [`push_html`](fn.push_html.html).
+    pub fn push_html<'a, I>(&self, s: &mut String, iter: I)
+    where
+        I: Iterator<Item = Event<'a>>,
+    {
+        HtmlWriter::new(iter, s, *self).run().unwrap();
+    }
+
+    /// Renders the events to HTML and writes it out to a writable stream. See
+    /// [`write_html`](fn.write_html.html).
+    ///
+    /// With [`heading_ids`](#method.heading_ids) enabled, headings are only
+    /// written once they end, and the ids seen so far are kept for the whole
+    /// document.
+    pub fn write_html<'a, I, W>(&self, writer: W, iter: I) -> io::Result<()>
+    where
+        I: Iterator<Item = Event<'a>>,
+        W: Write,
+    {
+        HtmlWriter::new(iter, WriteWrapper(writer), *self).run()
+    }
+}
+
+/// Turns heading text into an id the way GitHub does.
+fn github_slug(text: &str) -> String {
+    let mut slug = String::with_capacity(text.len());
+    for c in text.trim().chars() {
+        if c.is_alphanumeric() || c == '-' || c == '_' {
+            slug.extend(c.to_lowercase());
+        } else if c == ' ' {
+            slug.push('-');
+        }
+    }
+    slug
 }
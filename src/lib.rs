@@ -51,7 +51,16 @@
 // an unstable crate.
 #![cfg_attr(rustbuild, feature(staged_api, rustc_private))]
 #![cfg_attr(rustbuild, unstable(feature = "rustc_private", issue = "27812"))]
+#![cfg_attr(not(feature = "std"), no_std)]
 
+// Collections and strings are imported from `alloc` everywhere. With `std`, it
+// is an alias, as the `alloc` crate itself requires a newer compiler.
+#[cfg(not(feature = "std"))]
+extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std as alloc;
+
+#[cfg(feature = "std")]
 pub mod html;
 
 #[macro_use]
@@ -59,6 +68,7 @@ extern crate bitflags;
 extern crate unicase;
 
 mod entities;
+#[cfg(feature = "std")]
 mod escape;
 mod linklabel;
 mod parse;
@@ -71,6 +81,8 @@ mod tree;
 mod simd;
 
 pub use crate::parse::{
-    Alignment, CodeBlockKind, Event, LinkType, OffsetIter, Options, Parser, Tag,
+    line_column, parse_to_events, AdmonitionKind, Alignment, BrokenLinkCallback, CodeBlockKind,
+    DepthIter, Event, FilterHtml, HardBreakStyle, LinkType, OffsetIter, Options, Parser, Tag,
+    TooLarge, UnresolvedReferenceCallback, Visit, Visitor,
 };
 pub use crate::strings::{CowStr, InlineStr};
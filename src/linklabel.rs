@@ -20,6 +20,7 @@
 
 //! Link label parsing and matching.
 
+use alloc::string::String;
 use unicase::UniCase;
 
 use crate::scanners::{is_ascii_whitespace, scan_eol};
@@ -94,7 +95,8 @@ pub(crate) fn scan_link_label_rest<'t>(
             b => {
                 only_white_space = false;
                 ix += 1;
-                if b & 0b1000_0000 != 0 {
+                // count every byte except utf-8 continuation bytes
+                if b & 0b1100_0000 != 0b1000_0000 {
                     codepoints += 1;
                 }
             }
@@ -127,6 +129,22 @@ mod test {
         assert_eq!(expected_output, normalized_label.as_ref());
     }
 
+    #[test]
+    fn label_length_limit() {
+        let label = "a".repeat(999) + "]";
+        assert_eq!(
+            Some(1000),
+            scan_link_label_rest(&label, &|_| None).map(|(n, _)| n)
+        );
+        let label = "ä".repeat(999) + "]";
+        assert_eq!(
+            Some(1999),
+            scan_link_label_rest(&label, &|_| None).map(|(n, _)| n)
+        );
+        let label = "a".repeat(1000) + "]";
+        assert!(scan_link_label_rest(&label, &|_| None).is_none());
+    }
+
     #[test]
     fn return_carriage_linefeed_ok() {
         let input = "hello\r\nworld\r\n]";
@@ -61,6 +61,41 @@ pub fn main() -> std::io::Result<()> {
         "enable GitHub-style strikethrough",
     );
     opts.optflag("L", "enable-tasklists", "enable GitHub-style task lists");
+    opts.optflag("M", "enable-math", "enable LaTeX-style math");
+    opts.optflag(
+        "A",
+        "enable-autolinks",
+        "enable GitHub-style bare URL autolinks",
+    );
+    opts.optflag(
+        "",
+        "enable-autolink-emails",
+        "also link bare email addresses, with --enable-autolinks",
+    );
+    opts.optflag(
+        "",
+        "enable-front-matter",
+        "emit YAML or TOML front matter as an event",
+    );
+    opts.optflag("W", "enable-wikilinks", "enable wiki-style [[Page]] links");
+    opts.optflag(
+        "P",
+        "enable-superscript",
+        "enable Pandoc-style ^superscript^",
+    );
+    opts.optflag("B", "enable-subscript", "enable Pandoc-style ~subscript~");
+    opts.optflag(
+        "",
+        "enable-admonitions",
+        "enable GitHub-style > [!NOTE] alerts",
+    );
+    opts.optflag("", "enable-spoiler", "enable Discord-style ||spoilers||");
+    opts.optflag("", "enable-highlight", "enable ==highlighted== text");
+    opts.optflag(
+        "",
+        "do-not-decode-entities",
+        "keep entities like &amp; undecoded in text",
+    );
 
     let matches = match opts.parse(&args[1..]) {
         Ok(m) => m,
@@ -86,6 +121,39 @@ pub fn main() -> std::io::Result<()> {
     if matches.opt_present("enable-tasklists") {
         opts.insert(Options::ENABLE_TASKLISTS);
     }
+    if matches.opt_present("enable-math") {
+        opts.insert(Options::ENABLE_MATH);
+    }
+    if matches.opt_present("enable-autolinks") {
+        opts.insert(Options::ENABLE_GFM_AUTOLINKS);
+    }
+    if matches.opt_present("enable-autolink-emails") {
+        opts.insert(Options::ENABLE_GFM_AUTOLINK_EMAIL_WITHOUT_MAILTO);
+    }
+    if matches.opt_present("enable-front-matter") {
+        opts.insert(Options::ENABLE_FRONT_MATTER);
+    }
+    if matches.opt_present("enable-wikilinks") {
+        opts.insert(Options::ENABLE_WIKILINKS);
+    }
+    if matches.opt_present("enable-superscript") {
+        opts.insert(Options::ENABLE_SUPERSCRIPT);
+    }
+    if matches.opt_present("enable-subscript") {
+        opts.insert(Options::ENABLE_SUBSCRIPT);
+    }
+    if matches.opt_present("enable-admonitions") {
+        opts.insert(Options::ENABLE_ADMONITIONS);
+    }
+    if matches.opt_present("enable-spoiler") {
+        opts.insert(Options::ENABLE_SPOILER);
+    }
+    if matches.opt_present("enable-highlight") {
+        opts.insert(Options::ENABLE_HIGHLIGHT);
+    }
+    if matches.opt_present("do-not-decode-entities") {
+        opts.insert(Options::DO_NOT_DECODE_ENTITIES);
+    }
 
     let mut input = String::new();
     io::stdin().lock().read_to_string(&mut input)?;
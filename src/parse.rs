@@ -20,9 +20,19 @@
 
 //! Tree-based two pass parser.
 
-use std::cmp::{max, min};
-use std::collections::{HashMap, VecDeque};
-use std::ops::{Index, Range};
+use alloc::collections::VecDeque;
+use alloc::string::String;
+use alloc::vec::Vec;
+use alloc::{format, vec};
+use core::cmp::{max, min};
+use core::fmt;
+use core::ops::{Index, Range};
+#[cfg(feature = "std")]
+use std::collections::HashMap;
+
+// Without `std` there is no hasher, so the maps are ordered instead.
+#[cfg(not(feature = "std"))]
+use alloc::collections::BTreeMap as HashMap;
 
 use unicase::UniCase;
 
@@ -38,12 +48,20 @@ use crate::tree::{Tree, TreeIndex, TreePointer};
 // https://spec.commonmark.org/0.29/#link-destination
 const LINK_MAX_NESTED_PARENS: usize = 5;
 
+// The indentation that starts an indented code block, per CommonMark.
+const DEFAULT_CODE_INDENT: usize = 4;
+
+// The schemes recognized by the GFM autolinks extension.
+const DEFAULT_AUTOLINK_SCHEMES: &[&str] = &["http", "https"];
+
 /// Codeblock kind.
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub enum CodeBlockKind<'a> {
     Indented,
-    /// The value contained in the tag describes the language of the code, which may be empty.
-    Fenced(CowStr<'a>),
+    /// The first value is the language of the code, which may be empty. It is the first
+    /// word of the info string, with backslash escapes and entities resolved. The second
+    /// value is the rest of the info string, verbatim.
+    Fenced(CowStr<'a>, CowStr<'a>),
 }
 
 impl<'a> CodeBlockKind<'a> {
@@ -56,14 +74,34 @@ impl<'a> CodeBlockKind<'a> {
 
     pub fn is_fenced(&self) -> bool {
         match *self {
-            CodeBlockKind::Fenced(_) => true,
+            CodeBlockKind::Fenced(..) => true,
             _ => false,
         }
     }
+
+    /// Converts into a `CodeBlockKind` that owns its strings.
+    pub fn into_static(self) -> CodeBlockKind<'static> {
+        match self {
+            CodeBlockKind::Indented => CodeBlockKind::Indented,
+            CodeBlockKind::Fenced(language, attributes) => {
+                CodeBlockKind::Fenced(language.into_static(), attributes.into_static())
+            }
+        }
+    }
+}
+
+/// The kind of an admonition, given by its `[!KIND]` marker.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum AdmonitionKind {
+    Note,
+    Tip,
+    Important,
+    Warning,
+    Caution,
 }
 
 /// Tags for elements that can contain other elements.
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub enum Tag<'a> {
     /// A paragraph of text and other inline elements.
     Paragraph,
@@ -72,13 +110,22 @@ pub enum Tag<'a> {
     Heading(u32),
 
     BlockQuote,
-    /// A code block.
+    /// A block quote whose first line is an admonition marker like `[!NOTE]`.
+    /// The marker line is not part of its content. Only emitted when
+    /// `Options::ENABLE_ADMONITIONS` is set.
+    Admonition(AdmonitionKind),
+    /// A code block. When iterating with offsets, the source range of the end event of
+    /// a closed fenced code block is its closing line, which may be indented and have a
+    /// longer fence than the opening one.
     CodeBlock(CodeBlockKind<'a>),
 
-    /// A list. If the list is ordered the field indicates the number of the first item.
-    /// Contains only list items.
-    List(Option<u64>), // TODO: add delim and tight for ast (not needed for html)
-    /// A list item.
+    /// A list. If the list is ordered the first field indicates the number of the first item.
+    /// The second field is the list marker character: `-`, `+` or `*` for bullet lists and
+    /// `.` or `)` for ordered lists. Contains only list items.
+    List(Option<u64>, char), // TODO: add tight for ast (not needed for html)
+    /// A list item. When iterating with offsets, the source range of its start event
+    /// runs from the indentation before the marker up to the item's content, like
+    /// `- ` or `  1. `.
     Item,
     /// A footnote definition. The value contained is the footnote's label by which it can
     /// be referred to.
@@ -94,9 +141,21 @@ pub enum Tag<'a> {
     TableCell,
 
     // span-level tags
+    /// Emphasis. When iterating with offsets, the source range of its start and end
+    /// events is the delimiter run, so the delimiter (`*` or `_`) is its first byte.
     Emphasis,
+    /// Strong emphasis. Its delimiter can be found the same way as for `Emphasis`.
     Strong,
     Strikethrough,
+    Superscript,
+    Subscript,
+    /// A spoiler like `||hidden||`. Only emitted when `Options::ENABLE_SPOILER`
+    /// is set.
+    Spoiler,
+    /// Highlighted text like `==marked==`. Only emitted when
+    /// `Options::ENABLE_HIGHLIGHT` is set, or by a delimiter registered with
+    /// [`Parser::register_delimiter`](struct.Parser.html#method.register_delimiter).
+    Highlight,
 
     /// A link. The first field is the link type, the second the destination URL and the third is a title.
     Link(LinkType, CowStr<'a>, CowStr<'a>),
@@ -105,8 +164,41 @@ pub enum Tag<'a> {
     Image(LinkType, CowStr<'a>, CowStr<'a>),
 }
 
+impl<'a> Tag<'a> {
+    /// Converts into a `Tag` that owns its strings.
+    pub fn into_static(self) -> Tag<'static> {
+        match self {
+            Tag::Paragraph => Tag::Paragraph,
+            Tag::Heading(level) => Tag::Heading(level),
+            Tag::BlockQuote => Tag::BlockQuote,
+            Tag::Admonition(kind) => Tag::Admonition(kind),
+            Tag::CodeBlock(kind) => Tag::CodeBlock(kind.into_static()),
+            Tag::List(start, c) => Tag::List(start, c),
+            Tag::Item => Tag::Item,
+            Tag::FootnoteDefinition(label) => Tag::FootnoteDefinition(label.into_static()),
+            Tag::Table(alignments) => Tag::Table(alignments),
+            Tag::TableHead => Tag::TableHead,
+            Tag::TableRow => Tag::TableRow,
+            Tag::TableCell => Tag::TableCell,
+            Tag::Emphasis => Tag::Emphasis,
+            Tag::Strong => Tag::Strong,
+            Tag::Strikethrough => Tag::Strikethrough,
+            Tag::Superscript => Tag::Superscript,
+            Tag::Subscript => Tag::Subscript,
+            Tag::Spoiler => Tag::Spoiler,
+            Tag::Highlight => Tag::Highlight,
+            Tag::Link(link_type, url, title) => {
+                Tag::Link(link_type, url.into_static(), title.into_static())
+            }
+            Tag::Image(link_type, url, title) => {
+                Tag::Image(link_type, url.into_static(), title.into_static())
+            }
+        }
+    }
+}
+
 /// Type specifier for inline links. See [the Tag::Link](enum.Tag.html#variant.Link) for more information.
-#[derive(Clone, Debug, PartialEq, Copy)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Copy)]
 pub enum LinkType {
     /// Inline link like `[foo](bar)`
     Inline,
@@ -126,6 +218,8 @@ pub enum LinkType {
     Autolink,
     /// Email address in autolink like `<john@example.org>`
     Email,
+    /// Wiki link like `[[Page]]` or `[[Page|Display]]`
+    WikiLink,
 }
 
 impl LinkType {
@@ -142,7 +236,7 @@ impl LinkType {
 /// Markdown events that are generated in a preorder traversal of the document
 /// tree, with additional `End` events whenever all of an inner node's children
 /// have been visited.
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub enum Event<'a> {
     /// Start of a tagged element. Events that are yielded after this event
     /// and before its corresponding `End` event are inside this element.
@@ -152,7 +246,8 @@ pub enum Event<'a> {
     End(Tag<'a>),
     /// A text node.
     Text(CowStr<'a>),
-    /// An inline code node.
+    /// An inline code node. Unlike code blocks, which are a `Tag::CodeBlock` element
+    /// containing `Text` events, a code span is this single event.
     Code(CowStr<'a>),
     /// An HTML node.
     Html(CowStr<'a>),
@@ -162,16 +257,52 @@ pub enum Event<'a> {
     FootnoteReference(CowStr<'a>),
     /// A soft line break.
     SoftBreak,
-    /// A hard line break.
-    HardBreak,
+    /// A hard line break, along with the form it was written in.
+    HardBreak(HardBreakStyle),
     /// A horizontal ruler.
     Rule,
     /// A task list marker, rendered as a checkbox in HTML. Contains a true when it is checked.
     TaskListMarker(bool),
+    /// Inline math delimited by single dollar signs. Contains the raw content
+    /// between the delimiters.
+    InlineMath(CowStr<'a>),
+    /// A block of display math delimited by double dollar signs. Contains the raw
+    /// content between the delimiters.
+    DisplayMath(CowStr<'a>),
+    /// A link reference definition. Contains its source text. Only emitted when
+    /// `Options::ENABLE_REFERENCE_DEFINITIONS` is set.
+    ReferenceDefinition(CowStr<'a>),
+    /// YAML or TOML front matter at the very start of the document. Contains the
+    /// raw content between the `---` or `+++` delimiter lines. Only emitted when
+    /// `Options::ENABLE_FRONT_MATTER` is set.
+    FrontMatter(CowStr<'a>),
+}
+
+impl<'a> Event<'a> {
+    /// Converts into an `Event` that owns its strings, so that it can outlive
+    /// the source it was parsed from.
+    pub fn into_static(self) -> Event<'static> {
+        match self {
+            Event::Start(tag) => Event::Start(tag.into_static()),
+            Event::End(tag) => Event::End(tag.into_static()),
+            Event::Text(text) => Event::Text(text.into_static()),
+            Event::Code(text) => Event::Code(text.into_static()),
+            Event::Html(html) => Event::Html(html.into_static()),
+            Event::FootnoteReference(label) => Event::FootnoteReference(label.into_static()),
+            Event::SoftBreak => Event::SoftBreak,
+            Event::HardBreak(style) => Event::HardBreak(style),
+            Event::Rule => Event::Rule,
+            Event::TaskListMarker(checked) => Event::TaskListMarker(checked),
+            Event::InlineMath(math) => Event::InlineMath(math.into_static()),
+            Event::DisplayMath(math) => Event::DisplayMath(math.into_static()),
+            Event::ReferenceDefinition(text) => Event::ReferenceDefinition(text.into_static()),
+            Event::FrontMatter(text) => Event::FrontMatter(text.into_static()),
+        }
+    }
 }
 
 /// Table column text alignment.
-#[derive(Copy, Clone, Debug, PartialEq)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub enum Alignment {
     /// Default text alignment.
     None,
@@ -180,6 +311,24 @@ pub enum Alignment {
     Right,
 }
 
+/// How a hard line break is written in the source.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum HardBreakStyle {
+    /// A backslash at the end of the line. This is the default, as it can't be
+    /// lost to editors trimming trailing whitespace.
+    Backslash,
+    /// Two or more spaces or tabs at the end of the line.
+    Spaces,
+}
+
+// `#[default]` on a variant needs a newer compiler than the crate supports
+#[allow(clippy::derivable_impls)]
+impl Default for HardBreakStyle {
+    fn default() -> Self {
+        HardBreakStyle::Backslash
+    }
+}
+
 bitflags! {
     /// Option struct containing flags for enabling extra features
     /// that are not part of the CommonMark spec.
@@ -188,6 +337,49 @@ bitflags! {
         const ENABLE_FOOTNOTES = 1 << 2;
         const ENABLE_STRIKETHROUGH = 1 << 3;
         const ENABLE_TASKLISTS = 1 << 4;
+        const ENABLE_MATH = 1 << 5;
+        /// Emit link reference definitions as `Event::ReferenceDefinition`
+        /// instead of dropping them from the event stream.
+        const ENABLE_REFERENCE_DEFINITIONS = 1 << 6;
+        /// Recognize bare URLs and `www.` links in text, following the GFM
+        /// autolinks extension. See `Parser::autolink_schemes`.
+        const ENABLE_GFM_AUTOLINKS = 1 << 7;
+        /// Parse wiki links like `[[Page]]` and `[[Page|Display]]`. The
+        /// destination is derived from the page name, unless the broken link
+        /// callback returns one.
+        const ENABLE_WIKILINKS = 1 << 8;
+        /// Parse Pandoc-style superscript like `^2^`.
+        const ENABLE_SUPERSCRIPT = 1 << 9;
+        /// Parse Pandoc-style subscript like `~2~`.
+        const ENABLE_SUBSCRIPT = 1 << 10;
+        /// Also recognize bare email addresses like `user@example.com` as links
+        /// when `ENABLE_GFM_AUTOLINKS` is set. Has no effect on its own.
+        const ENABLE_GFM_AUTOLINK_EMAIL_WITHOUT_MAILTO = 1 << 11;
+        /// Emit front matter delimited by `---` (YAML) or `+++` (TOML) lines at
+        /// the start of the document as `Event::FrontMatter`.
+        const ENABLE_FRONT_MATTER = 1 << 12;
+        /// Emit the content of each code block as a single `Text` event. It
+        /// borrows from the source when the content is contiguous there, and is
+        /// assembled into an owned string when lines had to be normalized, such
+        /// as for CRLF line endings or container prefixes like `> `.
+        const ENABLE_CODE_BLOCK_SINGLE_TEXT = 1 << 13;
+        /// Parse GitHub-style alerts, block quotes starting with a line like
+        /// `> [!NOTE]`, as `Tag::Admonition`. The kind is one of `NOTE`, `TIP`,
+        /// `IMPORTANT`, `WARNING` and `CAUTION`, in any case.
+        const ENABLE_ADMONITIONS = 1 << 14;
+        /// Parse Discord-style spoilers like `||hidden||`. They follow the same
+        /// rules as strikethrough, with runs of exactly two pipes. Inside tables,
+        /// pipes still separate cells.
+        const ENABLE_SPOILER = 1 << 15;
+        /// Keep entity and numeric character references like `&amp;` as they
+        /// appear in the source in `Text` events, for consumers that serialize
+        /// the events back to markdown. They are still decoded in link
+        /// destinations and titles.
+        const DO_NOT_DECODE_ENTITIES = 1 << 16;
+        /// Parse highlighted text like `==marked==` as `Tag::Highlight`. It
+        /// follows the same rules as strikethrough, with runs of exactly two
+        /// equals signs.
+        const ENABLE_HIGHLIGHT = 1 << 17;
     }
 }
 
@@ -203,7 +395,7 @@ enum ItemBody {
     Paragraph,
     Text,
     SoftBreak,
-    HardBreak,
+    HardBreak(HardBreakStyle),
 
     // These are possible inline items, need to be resolved in second pass.
 
@@ -214,23 +406,35 @@ enum ItemBody {
     MaybeLinkOpen,
     MaybeLinkClose,
     MaybeImage,
+    MaybeWikiLink,
+    MaybeMath(bool, bool), // can_open, can_close
 
     // These are inline items after resolution.
     Emphasis,
     Strong,
     Strikethrough,
+    Superscript,
+    Subscript,
+    Spoiler,
+    // span delimited by a registered delimiter
+    CustomTag(TagIndex),
     Code(CowIndex),
     Link(LinkIndex),
     Image(LinkIndex),
     FootnoteReference(CowIndex),
     TaskListMarker(bool), // true for checked
+    InlineMath,
 
     Rule,
     Heading(u32), // heading level
-    FencedCodeBlock(CowIndex),
+    FencedCodeBlock(CodeInfoIndex),
     IndentCodeBlock,
+    DisplayMath(CowIndex),
+    ReferenceDefinition,
+    FrontMatter,
     Html,
     BlockQuote,
+    Admonition(AdmonitionKind),
     List(bool, u8, u64), // is_tight, list character, list start index
     ListItem(usize),     // indent level
     SynthesizeText(CowIndex),
@@ -254,7 +458,20 @@ impl<'a> ItemBody {
             | ItemBody::MaybeCode(..)
             | ItemBody::MaybeLinkOpen
             | ItemBody::MaybeLinkClose
-            | ItemBody::MaybeImage => true,
+            | ItemBody::MaybeImage
+            | ItemBody::MaybeWikiLink
+            | ItemBody::MaybeMath(..) => true,
+            _ => false,
+        }
+    }
+
+    /// Whether the children of this item can be inline content.
+    fn has_inline_content(&self) -> bool {
+        match *self {
+            ItemBody::Paragraph
+            | ItemBody::Heading(_)
+            | ItemBody::TableCell
+            | ItemBody::ListItem(_) => true,
             _ => false,
         }
     }
@@ -289,17 +506,30 @@ struct FirstPass<'a> {
     allocs: Allocations<'a>,
     options: Options,
     list_nesting: usize,
+    // number of spaces of indentation that start an indented code block
+    code_indent: usize,
 }
 
 impl<'a> FirstPass<'a> {
-    fn new(text: &'a str, options: Options) -> FirstPass {
+    fn new(text: &'a str, options: Options, code_indent: usize) -> FirstPass {
         // This is a very naive heuristic for the number of nodes
         // we'll need.
         let start_capacity = max(128, text.len() / 32);
         let tree = Tree::with_capacity(start_capacity);
+        FirstPass::with_tree(text, options, code_indent, tree, Allocations::new())
+    }
+
+    /// Creates a first pass that adds blocks to an existing tree, after its
+    /// current node.
+    fn with_tree(
+        text: &'a str,
+        options: Options,
+        code_indent: usize,
+        tree: Tree<Item>,
+        allocs: Allocations<'a>,
+    ) -> FirstPass<'a> {
         let begin_list_item = false;
         let last_line_blank = false;
-        let allocs = Allocations::new();
         FirstPass {
             text,
             tree,
@@ -308,11 +538,22 @@ impl<'a> FirstPass<'a> {
             allocs,
             options,
             list_nesting: 0,
+            code_indent,
         }
     }
 
     fn run(mut self) -> (Tree<Item>, Allocations<'a>) {
         let mut ix = 0;
+        if self.options.contains(Options::ENABLE_FRONT_MATTER) {
+            if let Some((content, len)) = scan_front_matter(self.text.as_bytes()) {
+                self.tree.append(Item {
+                    start: content.start,
+                    end: content.end,
+                    body: ItemBody::FrontMatter,
+                });
+                ix = len;
+            }
+        }
         while ix < self.text.len() {
             ix = self.parse_block(ix);
         }
@@ -322,6 +563,22 @@ impl<'a> FirstPass<'a> {
         (self.tree, self.allocs)
     }
 
+    /// Parses blocks from `ix` on, until the end of the text or until the
+    /// first line outside any container for which `resume` returns true.
+    /// Returns the offset parsing stopped at.
+    fn run_region(&mut self, mut ix: usize, mut resume: impl FnMut(usize) -> bool) -> usize {
+        while ix < self.text.len() {
+            if self.tree.spine_len() == 0 && resume(ix) {
+                return ix;
+            }
+            ix = self.parse_block(ix);
+        }
+        for _ in 0..self.tree.spine_len() {
+            self.pop(ix);
+        }
+        ix
+    }
+
     /// Returns offset after block.
     fn parse_block(&mut self, mut start_ix: usize) -> usize {
         let bytes = self.text.as_bytes();
@@ -356,7 +613,7 @@ impl<'a> FirstPass<'a> {
         // Process new containers
         loop {
             let container_start = start_ix + line_start.bytes_scanned();
-            if let Some((ch, index, indent)) = line_start.scan_list_marker() {
+            if let Some((ch, index, indent)) = line_start.scan_list_marker(self.code_indent) {
                 let after_marker_index = start_ix + line_start.bytes_scanned();
                 self.continue_list(container_start, ch, index);
                 self.tree.append(Item {
@@ -380,10 +637,16 @@ impl<'a> FirstPass<'a> {
                 }
             } else if line_start.scan_blockquote_marker() {
                 self.finish_list(start_ix);
+                // the rest of the marker line is left blank
+                let admonition = if self.options.contains(Options::ENABLE_ADMONITIONS) {
+                    line_start.scan_admonition_marker()
+                } else {
+                    None
+                };
                 self.tree.append(Item {
                     start: container_start,
                     end: 0, // will get set later
-                    body: ItemBody::BlockQuote,
+                    body: admonition.map_or(ItemBody::BlockQuote, ItemBody::Admonition),
                 });
                 self.tree.push();
             } else {
@@ -396,7 +659,7 @@ impl<'a> FirstPass<'a> {
         if let Some(n) = scan_blank_line(&bytes[ix..]) {
             if let Some(node_ix) = self.tree.peek_up() {
                 match self.tree[node_ix].item.body {
-                    ItemBody::BlockQuote => (),
+                    ItemBody::BlockQuote | ItemBody::Admonition(..) => (),
                     _ => {
                         if self.begin_list_item {
                             // A list item can begin with at most one blank line.
@@ -415,8 +678,8 @@ impl<'a> FirstPass<'a> {
         // Save `remaining_space` here to avoid needing to backtrack `line_start` for HTML blocks
         let remaining_space = line_start.remaining_space();
 
-        let indent = line_start.scan_space_upto(4);
-        if indent == 4 {
+        let indent = line_start.scan_space_upto(self.code_indent);
+        if indent == self.code_indent {
             let ix = start_ix + line_start.bytes_scanned();
             let remaining_space = line_start.remaining_space();
             return self.parse_indented_code_block(ix, remaining_space);
@@ -454,7 +717,21 @@ impl<'a> FirstPass<'a> {
 
         // parse refdef
         if let Some((bytecount, label, link_def)) = self.parse_refdef_total(ix) {
-            self.allocs.refdefs.entry(label).or_insert(link_def);
+            let end =
+                ix + bytecount - scan_rev_while(&bytes[ix..(ix + bytecount)], is_ascii_whitespace);
+            let def_ix = self.allocs.refdefs.len();
+            self.allocs
+                .refdef_ixs
+                .entry(label.clone())
+                .or_insert(def_ix);
+            self.allocs.refdefs.push((label, link_def, ix..end));
+            if self.options.contains(Options::ENABLE_REFERENCE_DEFINITIONS) {
+                self.tree.append(Item {
+                    start: ix,
+                    end,
+                    body: ItemBody::ReferenceDefinition,
+                });
+            }
             let ix = ix + bytecount;
             // try to read trailing whitespace or it will register as a completely blank line
             // TODO: shouldn't we do this for all block level items?
@@ -464,6 +741,18 @@ impl<'a> FirstPass<'a> {
         if let Some((n, fence_ch)) = scan_code_fence(&bytes[ix..]) {
             return self.parse_fenced_code_block(ix, indent, fence_ch, n);
         }
+
+        if self.options.contains(Options::ENABLE_MATH) {
+            if let Some((end_ix, math)) = self.scan_display_math(ix) {
+                let cow_ix = self.allocs.allocate_cow(math);
+                self.tree.append(Item {
+                    start: ix,
+                    end: end_ix,
+                    body: ItemBody::DisplayMath(cow_ix),
+                });
+                return end_ix;
+            }
+        }
         self.parse_paragraph(ix)
     }
 
@@ -593,14 +882,14 @@ impl<'a> FirstPass<'a> {
             // break out when we find a table
             if let Some(Item {
                 body: ItemBody::Table(alignment_ix),
-                start,
                 end,
+                ..
             }) = brk
             {
                 let table_cols = self.allocs[alignment_ix].len();
                 self.tree[node_ix].item = Item {
                     body: ItemBody::Table(alignment_ix),
-                    start,
+                    start: start_ix,
                     end,
                 };
                 // this clears out any stuff we may have appended - but there may
@@ -614,32 +903,32 @@ impl<'a> FirstPass<'a> {
             ix = next_ix;
             let mut line_start = LineStart::new(&bytes[ix..]);
             let n_containers = scan_containers(&self.tree, &mut line_start);
-            if !line_start.scan_space(4) {
+            if !line_start.scan_space(self.code_indent) {
                 let ix_new = ix + line_start.bytes_scanned();
+                // a setext underline takes precedence over a thematic break or
+                // list item, but only when it's in the same container as the
+                // paragraph. `---` is an underline, but `- - -` and `- bar`
+                // aren't and are handled as interrupts below.
                 if n_containers == self.tree.spine_len() {
                     if let Some(ix_setext) = self.parse_setext_heading(ix_new, node_ix) {
-                        if let Some(Item {
-                            start,
-                            body: ItemBody::HardBreak,
-                            ..
-                        }) = brk
-                        {
-                            if bytes[start] == b'\\' {
-                                self.tree.append_text(start, start + 1);
-                            }
-                        }
+                        self.append_trailing_backslash(brk);
                         ix = ix_setext;
                         break;
                     }
                 }
                 // first check for non-empty lists, then for other interrupts
                 let suffix = &bytes[ix_new..];
-                if self.interrupt_paragraph_by_list(suffix) || scan_paragraph_interrupt(suffix) {
+                if self.interrupt_paragraph_by_list(suffix)
+                    || scan_paragraph_interrupt(suffix)
+                    || self.interrupt_paragraph_by_math(ix_new)
+                {
+                    self.append_trailing_backslash(brk);
                     break;
                 }
             }
             line_start.scan_all_space();
             if line_start.is_at_eol() {
+                self.append_trailing_backslash(brk);
                 break;
             }
             ix = next_ix + line_start.bytes_scanned();
@@ -652,6 +941,21 @@ impl<'a> FirstPass<'a> {
         ix
     }
 
+    /// A hard break can't end a block, so the backslash of a backslash hard
+    /// break on the last line of a paragraph is literal text.
+    fn append_trailing_backslash(&mut self, brk: Option<Item>) {
+        if let Some(Item {
+            start,
+            body: ItemBody::HardBreak(HardBreakStyle::Backslash),
+            ..
+        }) = brk
+        {
+            if self.text.as_bytes()[start] == b'\\' {
+                self.tree.append_text(start, start + 1);
+            }
+        }
+    }
+
     /// Returns end ix of setext_heading on success.
     fn parse_setext_heading(&mut self, ix: usize, node_ix: TreeIndex) -> Option<usize> {
         let bytes = self.text.as_bytes();
@@ -685,7 +989,6 @@ impl<'a> FirstPass<'a> {
                         return LoopInstruction::BreakAtWith(ix, None);
                     }
 
-                    let mut i = ix;
                     let eol_bytes = scan_eol(&bytes[ix..]).unwrap();
                     if mode == TableParseMode::Scan && pipes > 0 {
                         // check if we may be parsing a table
@@ -708,7 +1011,7 @@ impl<'a> FirstPass<'a> {
                                     return LoopInstruction::BreakAtWith(
                                         end_ix,
                                         Some(Item {
-                                            start: i,
+                                            start: ix,
                                             end: end_ix, // must update later
                                             body: ItemBody::Table(alignment_ix),
                                         }),
@@ -718,42 +1021,37 @@ impl<'a> FirstPass<'a> {
                         }
                     }
 
+                    // Break items span their trailing whitespace or backslash
+                    // and the line ending, so that their offsets point at the
+                    // syntax that produced them.
                     let end_ix = ix + eol_bytes;
                     let trailing_backslashes = scan_rev_while(&bytes[..ix], |b| b == b'\\');
                     if trailing_backslashes % 2 == 1 && end_ix < self.text.len() {
-                        i -= 1;
-                        self.tree.append_text(begin_text, i);
+                        self.tree.append_text(begin_text, ix - 1);
                         return LoopInstruction::BreakAtWith(
                             end_ix,
                             Some(Item {
-                                start: i,
+                                start: ix - 1,
                                 end: end_ix,
-                                body: ItemBody::HardBreak,
+                                body: ItemBody::HardBreak(HardBreakStyle::Backslash),
                             }),
                         );
                     }
                     let trailing_whitespace =
                         scan_rev_while(&bytes[..ix], is_ascii_whitespace_no_nl);
-                    if trailing_whitespace >= 2 {
-                        i -= trailing_whitespace;
-                        self.tree.append_text(begin_text, i);
-                        return LoopInstruction::BreakAtWith(
-                            end_ix,
-                            Some(Item {
-                                start: i,
-                                end: end_ix,
-                                body: ItemBody::HardBreak,
-                            }),
-                        );
-                    }
-
-                    self.tree.append_text(begin_text, ix);
+                    let break_start = ix - trailing_whitespace;
+                    let body = if trailing_whitespace >= 2 {
+                        ItemBody::HardBreak(HardBreakStyle::Spaces)
+                    } else {
+                        ItemBody::SoftBreak
+                    };
+                    self.tree.append_text(begin_text, break_start);
                     LoopInstruction::BreakAtWith(
                         end_ix,
                         Some(Item {
-                            start: i,
+                            start: break_start,
                             end: end_ix,
-                            body: ItemBody::SoftBreak,
+                            body,
                         }),
                     )
                 }
@@ -777,13 +1075,31 @@ impl<'a> FirstPass<'a> {
                         LoopInstruction::ContinueAndSkip(0)
                     }
                 }
-                c @ b'*' | c @ b'_' | c @ b'~' => {
+                c @ b'*' | c @ b'_' | c @ b'~' | c @ b'^' => {
                     let string_suffix = &self.text[ix..];
                     let count = 1 + scan_ch_repeat(&string_suffix.as_bytes()[1..], c);
-                    let can_open = delim_run_can_open(self.text, string_suffix, count, ix);
-                    let can_close = delim_run_can_close(self.text, string_suffix, count, ix);
-                    let is_valid_seq = c != b'~'
-                        || count == 2 && self.options.contains(Options::ENABLE_STRIKETHROUGH);
+                    let is_script = count == 1
+                        && (c == b'^' && self.options.contains(Options::ENABLE_SUPERSCRIPT)
+                            || c == b'~' && self.options.contains(Options::ENABLE_SUBSCRIPT));
+                    let (can_open, can_close) = if is_script {
+                        // scripts can only open when closed on the same line without
+                        // whitespace in between
+                        (
+                            scan_script(&bytes[ix..], c).is_some(),
+                            ix > 0 && !is_ascii_whitespace(bytes[ix - 1]),
+                        )
+                    } else {
+                        (
+                            delim_run_can_open(self.text, string_suffix, count, ix),
+                            delim_run_can_close(self.text, string_suffix, count, ix),
+                        )
+                    };
+                    let is_valid_seq = is_script
+                        || c == b'*'
+                        || c == b'_'
+                        || c == b'~'
+                            && count == 2
+                            && self.options.contains(Options::ENABLE_STRIKETHROUGH);
 
                     if (can_open || can_close) && is_valid_seq {
                         self.tree.append_text(begin_text, ix);
@@ -810,8 +1126,12 @@ impl<'a> FirstPass<'a> {
                     LoopInstruction::ContinueAndSkip(count - 1)
                 }
                 b'<' => {
-                    // Note: could detect some non-HTML cases and early escape here, but not
-                    // clear that's a win.
+                    // a `<` followed by whitespace or another `<` can start neither
+                    // inline html nor an autolink, so leave it in the text
+                    let next = bytes.get(ix + 1).cloned().unwrap_or(b'\n');
+                    if next == b'<' || is_ascii_whitespace(next) {
+                        return LoopInstruction::ContinueAndSkip(0);
+                    }
                     self.tree.append_text(begin_text, ix);
                     self.tree.append(Item {
                         start: ix,
@@ -836,6 +1156,18 @@ impl<'a> FirstPass<'a> {
                     }
                 }
                 b'[' => {
+                    if self.options.contains(Options::ENABLE_WIKILINKS)
+                        && scan_wikilink(&bytes[ix..]).is_some()
+                    {
+                        self.tree.append_text(begin_text, ix);
+                        self.tree.append(Item {
+                            start: ix,
+                            end: ix + 2,
+                            body: ItemBody::MaybeWikiLink,
+                        });
+                        begin_text = ix + 2;
+                        return LoopInstruction::ContinueAndSkip(1);
+                    }
                     self.tree.append_text(begin_text, ix);
                     self.tree.append(Item {
                         start: ix,
@@ -855,6 +1187,9 @@ impl<'a> FirstPass<'a> {
                     begin_text = ix + 1;
                     LoopInstruction::ContinueAndSkip(0)
                 }
+                b'&' if self.options.contains(Options::DO_NOT_DECODE_ENTITIES) => {
+                    LoopInstruction::ContinueAndSkip(0)
+                }
                 b'&' => match scan_entity(&bytes[ix..]) {
                     (n, Some(value)) => {
                         self.tree.append_text(begin_text, ix);
@@ -868,37 +1203,147 @@ impl<'a> FirstPass<'a> {
                     }
                     _ => LoopInstruction::ContinueAndSkip(0),
                 },
+                b'$' => {
+                    if !self.options.contains(Options::ENABLE_MATH) {
+                        return LoopInstruction::ContinueAndSkip(0);
+                    }
+                    let count = 1 + scan_ch_repeat(&bytes[(ix + 1)..], b'$');
+                    if count > 1 {
+                        // display math is block-level, runs of dollar signs are text
+                        return LoopInstruction::ContinueAndSkip(count - 1);
+                    }
+                    // an opening dollar must not be followed by whitespace, and a
+                    // closing one must neither be preceded by whitespace nor
+                    // followed by a digit
+                    let (can_open, next_is_digit) = match bytes.get(ix + 1) {
+                        Some(&c) => (!is_ascii_whitespace(c), c.is_ascii_digit()),
+                        None => (false, false),
+                    };
+                    let can_close = ix > 0 && !is_ascii_whitespace(bytes[ix - 1]) && !next_is_digit;
+                    if can_open || can_close {
+                        self.tree.append_text(begin_text, ix);
+                        self.tree.append(Item {
+                            start: ix,
+                            end: ix + 1,
+                            body: ItemBody::MaybeMath(can_open, can_close),
+                        });
+                        begin_text = ix + 1;
+                    }
+                    LoopInstruction::ContinueAndSkip(0)
+                }
                 b'|' => {
                     if let TableParseMode::Active = mode {
-                        LoopInstruction::BreakAtWith(ix, None)
+                        return LoopInstruction::BreakAtWith(ix, None);
+                    }
+                    let count = if self.options.contains(Options::ENABLE_SPOILER) {
+                        1 + scan_ch_repeat(&bytes[(ix + 1)..], b'|')
                     } else {
-                        last_pipe_ix = ix;
-                        pipes += 1;
-                        LoopInstruction::ContinueAndSkip(0)
+                        1
+                    };
+                    last_pipe_ix = ix + count - 1;
+                    pipes += count;
+                    if count == 2 {
+                        let string_suffix = &self.text[ix..];
+                        let can_open = delim_run_can_open(self.text, string_suffix, count, ix);
+                        let can_close = delim_run_can_close(self.text, string_suffix, count, ix);
+                        if can_open || can_close {
+                            self.tree.append_text(begin_text, ix);
+                            for i in 0..count {
+                                self.tree.append(Item {
+                                    start: ix + i,
+                                    end: ix + i + 1,
+                                    body: ItemBody::MaybeEmphasis(count - i, can_open, can_close),
+                                });
+                            }
+                            begin_text = ix + count;
+                        }
                     }
+                    LoopInstruction::ContinueAndSkip(count - 1)
                 }
                 _ => LoopInstruction::ContinueAndSkip(0),
             }
         });
 
         if brk.is_none() {
-            // need to close text at eof
-            self.tree.append_text(begin_text, final_ix);
+            // need to close text at eof. outside of tables, trailing whitespace
+            // is dropped as it can't form a hard break here
+            let end = if mode == TableParseMode::Active {
+                final_ix
+            } else {
+                final_ix - scan_rev_while(&bytes[begin_text..final_ix], is_ascii_whitespace_no_nl)
+            };
+            self.tree.append_text(begin_text, end);
         }
         (final_ix, brk)
     }
 
     /// Check whether we should allow a paragraph interrupt by lists. Only non-empty
-    /// lists are allowed.
+    /// bullet lists and non-empty ordered lists starting at 1 are allowed.
     fn interrupt_paragraph_by_list(&self, suffix: &[u8]) -> bool {
-        scan_listitem(suffix).map_or(false, |(ix, delim, index, _)| {
+        scan_listitem(suffix, self.code_indent).map_or(false, |(ix, delim, index, _)| {
+            let is_ordered = delim == b'.' || delim == b')';
             self.list_nesting > 0 ||
             // we don't allow interruption by either empty lists or
             // numbered lists starting at an index other than 1
-            !scan_empty_list(&suffix[ix..]) && (delim == b'*' || delim == b'-' || index == 1)
+            !scan_empty_list(&suffix[ix..]) && (!is_ordered || index == 1)
         })
     }
 
+    /// Check whether we should allow a paragraph interrupt by display math. Only
+    /// display math blocks that are closed are allowed.
+    fn interrupt_paragraph_by_math(&self, ix: usize) -> bool {
+        self.options.contains(Options::ENABLE_MATH) && self.scan_display_math(ix).is_some()
+    }
+
+    /// Scans a display math block whose opening `$$` starts at `start_ix`. The
+    /// block is closed by a line ending in `$$` and cannot contain blank lines.
+    ///
+    /// Returns the offset of the first line after the block and the raw content
+    /// between the delimiters on success.
+    fn scan_display_math(&self, start_ix: usize) -> Option<(usize, CowStr<'a>)> {
+        let bytes = self.text.as_bytes();
+        if !bytes[start_ix..].starts_with(b"$$") {
+            return None;
+        }
+        let mut ix = start_ix + 2;
+        let mut segment_start = ix;
+        // only allocates when the content is interrupted by container markers
+        let mut buf: Option<String> = None;
+
+        loop {
+            let next_line_ix = ix + scan_nextline(&bytes[ix..]);
+            let line_end =
+                next_line_ix - scan_rev_while(&bytes[ix..next_line_ix], is_ascii_whitespace);
+            if line_end >= ix + 2 && bytes[..line_end].ends_with(b"$$") {
+                let close_ix = line_end - 2;
+                let math = if let Some(mut buf) = buf {
+                    buf.push_str(&self.text[segment_start..close_ix]);
+                    buf.into()
+                } else {
+                    self.text[segment_start..close_ix].into()
+                };
+                return Some((next_line_ix, math));
+            }
+            if next_line_ix == self.text.len() {
+                return None;
+            }
+
+            let mut line_start = LineStart::new(&bytes[next_line_ix..]);
+            if scan_containers(&self.tree, &mut line_start) != self.tree.spine_len() {
+                return None;
+            }
+            ix = next_line_ix + line_start.bytes_scanned();
+            if scan_blank_line(&bytes[ix..]).is_some() {
+                return None;
+            }
+            if ix > next_line_ix {
+                buf.get_or_insert_with(String::new)
+                    .push_str(&self.text[segment_start..next_line_ix]);
+                segment_start = ix;
+            }
+        }
+    }
+
     /// When start_ix is at the beginning of an HTML block of type 1 to 5,
     /// this will find the end of the block, adding the block itself to the
     /// tree and also keeping track of the lines of HTML within the block.
@@ -998,7 +1443,7 @@ impl<'a> FirstPass<'a> {
             let mut line_start = LineStart::new(&bytes[ix..]);
             let n_containers = scan_containers(&self.tree, &mut line_start);
             if n_containers < self.tree.spine_len()
-                || !(line_start.scan_space(4) || line_start.is_at_eol())
+                || !(line_start.scan_space(self.code_indent) || line_start.is_at_eol())
             {
                 break;
             }
@@ -1016,6 +1461,12 @@ impl<'a> FirstPass<'a> {
             self.tree[child].next = TreePointer::Nil;
             self.tree[child].item.end = last_nonblank_ix;
         }
+        if self
+            .options
+            .contains(Options::ENABLE_CODE_BLOCK_SINGLE_TEXT)
+        {
+            self.merge_code_text();
+        }
         self.pop(end_ix);
         ix
     }
@@ -1034,11 +1485,16 @@ impl<'a> FirstPass<'a> {
         // to just do a forward scan here?
         let mut ix = info_start + scan_nextline(&bytes[info_start..]);
         let info_end = ix - scan_rev_while(&bytes[info_start..ix], is_ascii_whitespace);
-        let info_string = unescape(&self.text[info_start..info_end]);
+        let language_end =
+            info_start + scan_while(&bytes[info_start..info_end], |c| !is_ascii_whitespace(c));
+        let attributes_start =
+            language_end + scan_while(&bytes[language_end..info_end], is_ascii_whitespace);
+        let language = unescape(&self.text[info_start..language_end]);
+        let attributes = self.text[attributes_start..info_end].into();
         self.tree.append(Item {
             start: start_ix,
             end: 0, // will get set later
-            body: ItemBody::FencedCodeBlock(self.allocs.allocate_cow(info_string)),
+            body: ItemBody::FencedCodeBlock(self.allocs.allocate_code_info(language, attributes)),
         });
         self.tree.push();
         loop {
@@ -1064,6 +1520,12 @@ impl<'a> FirstPass<'a> {
             ix = next_ix;
         }
 
+        if self
+            .options
+            .contains(Options::ENABLE_CODE_BLOCK_SINGLE_TEXT)
+        {
+            self.merge_code_text();
+        }
         self.pop(ix);
 
         // try to read trailing whitespace or it will register as a completely blank line
@@ -1088,6 +1550,32 @@ impl<'a> FirstPass<'a> {
         }
     }
 
+    /// Replaces the text items of the current code block by a single one, if
+    /// there is more than one.
+    fn merge_code_text(&mut self) {
+        let block_ix = self.tree.peek_up().unwrap();
+        let first_ix = match self.tree[block_ix].child {
+            TreePointer::Valid(ix) if self.tree[ix].next != TreePointer::Nil => ix,
+            _ => return,
+        };
+        let mut content = String::new();
+        let mut end = 0;
+        let mut cur = TreePointer::Valid(first_ix);
+        while let TreePointer::Valid(cur_ix) = cur {
+            let item = self.tree[cur_ix].item;
+            match item.body {
+                ItemBody::SynthesizeText(cow_ix) => content.push_str(&self.allocs[cow_ix]),
+                _ => content.push_str(&self.text[item.start..item.end]),
+            }
+            end = item.end;
+            cur = self.tree[cur_ix].next;
+        }
+        let cow_ix = self.allocs.allocate_cow(content.into());
+        self.tree[first_ix].item.end = end;
+        self.tree[first_ix].item.body = ItemBody::SynthesizeText(cow_ix);
+        self.tree[first_ix].next = TreePointer::Nil;
+    }
+
     /// Appends a line of HTML to the tree.
     fn append_html_line(&mut self, remaining_space: usize, start: usize, end: usize) {
         if remaining_space > 0 {
@@ -1124,8 +1612,13 @@ impl<'a> FirstPass<'a> {
     fn pop(&mut self, ix: usize) {
         let cur_ix = self.tree.pop().unwrap();
         self.tree[cur_ix].item.end = ix;
-        if let ItemBody::List(true, _, _) = self.tree[cur_ix].item.body {
-            surgerize_tight_list(&mut self.tree, cur_ix);
+        if let ItemBody::List(is_tight, _, _) = self.tree[cur_ix].item.body {
+            // lists are only opened for an item, so they can't be empty
+            debug_assert!(self.tree[cur_ix].child != TreePointer::Nil);
+            self.list_nesting -= 1;
+            if is_tight {
+                surgerize_tight_list(&mut self.tree, cur_ix);
+            }
         }
     }
 
@@ -1134,7 +1627,6 @@ impl<'a> FirstPass<'a> {
         if let Some(node_ix) = self.tree.peek_up() {
             if let ItemBody::List(_, _, _) = self.tree[node_ix].item.body {
                 self.pop(ix);
-                self.list_nesting -= 1;
             }
         }
         if self.last_line_blank {
@@ -1216,7 +1708,7 @@ impl<'a> FirstPass<'a> {
             let header_text = &bytes[header_start..ix];
             let mut limit = header_text
                 .iter()
-                .rposition(|&b| !(b == b'\n' || b == b'\r' || b == b' '))
+                .rposition(|&b| !(b == b'\n' || b == b'\r' || b == b' ' || b == b'\t'))
                 .map_or(0, |i| i + 1);
             let closer = header_text[..limit]
                 .iter()
@@ -1225,7 +1717,7 @@ impl<'a> FirstPass<'a> {
             if closer == 0 {
                 limit = closer;
             } else {
-                let spaces = scan_rev_while(&header_text[..closer], |b| b == b' ');
+                let spaces = scan_rev_while(&header_text[..closer], |b| b == b' ' || b == b'\t');
                 if spaces > 0 {
                     limit = closer - spaces;
                 }
@@ -1381,7 +1873,7 @@ fn scan_containers(tree: &Tree<Item>, line_start: &mut LineStart) -> usize {
     let mut i = 0;
     for &node_ix in tree.walk_spine() {
         match tree[node_ix].item.body {
-            ItemBody::BlockQuote => {
+            ItemBody::BlockQuote | ItemBody::Admonition(..) => {
                 let save = line_start.clone();
                 if !line_start.scan_blockquote_marker() {
                     *line_start = save;
@@ -1397,6 +1889,8 @@ fn scan_containers(tree: &Tree<Item>, line_start: &mut LineStart) -> usize {
                     }
                 }
             }
+            // Lists, footnote definitions and tables continue without a
+            // marker of their own.
             _ => (),
         }
         i += 1;
@@ -1565,7 +2059,7 @@ fn get_html_end_tag(text_bytes: &[u8]) -> Option<&'static str> {
 struct InlineEl {
     start: TreeIndex, // offset of tree node
     count: usize,
-    c: u8,      // b'*' or b'_'
+    c: u8,      // b'*', b'_', b'~', b'^', b'|' or a registered delimiter
     both: bool, // can both open and close
 }
 
@@ -1576,7 +2070,9 @@ struct InlineStack {
     // a strikethrough delimiter will never match with any element
     // in the stack with index smaller than
     // `lower_bounds[InlineStack::TILDES]`.
-    lower_bounds: [usize; 7],
+    lower_bounds: [usize; 10],
+    // lower bounds for registered delimiters, by character and run length
+    custom_lower_bounds: HashMap<(u8, usize), usize>,
 }
 
 impl InlineStack {
@@ -1588,14 +2084,25 @@ impl InlineStack {
     const ASTERISK_BASE: usize = 2;
     const TILDES: usize = 5;
     const UNDERSCORE_BOTH: usize = 6;
+    const SUBSCRIPT: usize = 7;
+    const SUPERSCRIPT: usize = 8;
+    const PIPES: usize = 9;
 
     fn pop_all(&mut self, tree: &mut Tree<Item>) {
-        for el in self.stack.drain(..) {
+        self.pop_to(tree, 0);
+        self.lower_bounds = [0; 10];
+        self.custom_lower_bounds.clear();
+    }
+
+    /// Truncates the stack to the given length, turning the delimiters of all
+    /// removed elements into text.
+    fn pop_to(&mut self, tree: &mut Tree<Item>, new_len: usize) {
+        let new_len = min(new_len, self.stack.len());
+        for el in self.stack.drain(new_len..) {
             for i in 0..el.count {
                 tree[el.start + i].item.body = ItemBody::Text;
             }
         }
-        self.lower_bounds = [0; 7];
     }
 
     fn get_lowerbound(&self, c: u8, count: usize, both: bool) -> usize {
@@ -1615,8 +2122,19 @@ impl InlineStack {
                     self.lower_bounds[InlineStack::ASTERISK_NOT_BOTH],
                 )
             }
-        } else {
+        } else if c == b'~' && count == 2 {
             self.lower_bounds[InlineStack::TILDES]
+        } else if c == b'~' {
+            self.lower_bounds[InlineStack::SUBSCRIPT]
+        } else if c == b'|' {
+            self.lower_bounds[InlineStack::PIPES]
+        } else if c == b'^' {
+            self.lower_bounds[InlineStack::SUPERSCRIPT]
+        } else {
+            self.custom_lower_bounds
+                .get(&(c, count))
+                .cloned()
+                .unwrap_or(0)
         }
     }
 
@@ -1632,8 +2150,16 @@ impl InlineStack {
             if !both {
                 self.lower_bounds[InlineStack::ASTERISK_NOT_BOTH] = new_bound;
             }
-        } else {
+        } else if c == b'~' && count == 2 {
             self.lower_bounds[InlineStack::TILDES] = new_bound;
+        } else if c == b'~' {
+            self.lower_bounds[InlineStack::SUBSCRIPT] = new_bound;
+        } else if c == b'|' {
+            self.lower_bounds[InlineStack::PIPES] = new_bound;
+        } else if c == b'^' {
+            self.lower_bounds[InlineStack::SUPERSCRIPT] = new_bound;
+        } else {
+            self.custom_lower_bounds.insert((c, count), new_bound);
         }
     }
 
@@ -1650,16 +2176,15 @@ impl InlineStack {
             .cloned()
             .enumerate()
             .rfind(|(_, el)| {
-                el.c == c && (!both && !el.both || (count + el.count) % 3 != 0 || count % 3 == 0)
+                // delimiters other than emphasis only match runs of equal length
+                el.c == c
+                    && (c == b'*' || c == b'_' || el.count == count)
+                    && (!both && !el.both || (count + el.count) % 3 != 0 || count % 3 == 0)
             });
 
         if let Some((matching_ix, matching_el)) = res {
             let matching_ix = matching_ix + lowerbound;
-            for el in &self.stack[(matching_ix + 1)..] {
-                for i in 0..el.count {
-                    tree[el.start + i].item.body = ItemBody::Text;
-                }
-            }
+            self.pop_to(tree, matching_ix + 1);
             self.stack.truncate(matching_ix);
             Some(matching_el)
         } else {
@@ -1675,10 +2200,10 @@ impl InlineStack {
 
 #[derive(Debug, Clone)]
 enum RefScan<'a> {
-    // label, next node index
-    LinkLabel(CowStr<'a>, TreePointer),
-    // contains next node index
-    Collapsed(TreePointer),
+    // label, next node index, end of the reference
+    LinkLabel(CowStr<'a>, TreePointer, usize),
+    // next node index, end of the reference
+    Collapsed(TreePointer, usize),
     Failed,
 }
 
@@ -1730,10 +2255,10 @@ fn scan_reference<'a, 'b>(tree: &'a Tree<Item>, text: &'b str, cur: TreePointer)
 
     if tail.starts_with(b"[]") {
         let closing_node = tree[cur_ix].next.unwrap();
-        RefScan::Collapsed(tree[closing_node].next)
+        RefScan::Collapsed(tree[closing_node].next, start + 2)
     } else if let Some((ix, ReferenceLabel::Link(label))) = scan_link_label(tree, &text[start..]) {
         let next_node = scan_nodes_to_ix(tree, cur, start + ix);
-        RefScan::LinkLabel(label, next_node)
+        RefScan::LinkLabel(label, next_node, start + ix)
     } else {
         RefScan::Failed
     }
@@ -1752,7 +2277,7 @@ impl LinkStack {
 
     fn pop(&mut self) -> Option<LinkStackEl> {
         let el = self.inner.pop();
-        self.disabled_ix = std::cmp::min(self.disabled_ix, self.inner.len());
+        self.disabled_ix = min(self.disabled_ix, self.inner.len());
         el
     }
 
@@ -1781,6 +2306,7 @@ struct LinkStackEl {
 enum LinkStackTy {
     Link,
     Image,
+    WikiLink,
     Disabled,
 }
 
@@ -1846,21 +2372,35 @@ struct CowIndex(usize);
 #[derive(Copy, Clone, PartialEq, Eq, Debug)]
 struct AlignmentIndex(usize);
 
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+struct TagIndex(usize);
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+struct CodeInfoIndex(usize);
+
 #[derive(Clone)]
 struct Allocations<'a> {
-    refdefs: HashMap<LinkLabel<'a>, LinkDef<'a>>,
+    // all link reference definitions with their source ranges, in source order
+    refdefs: Vec<(LinkLabel<'a>, LinkDef<'a>, Range<usize>)>,
+    // index into `refdefs` of the first definition of each label
+    refdef_ixs: HashMap<LinkLabel<'a>, usize>,
     links: Vec<(LinkType, CowStr<'a>, CowStr<'a>)>,
     cows: Vec<CowStr<'a>>,
     alignments: Vec<Vec<Alignment>>,
+    code_infos: Vec<(CowStr<'a>, CowStr<'a>)>,
+    tags: Vec<Tag<'a>>,
 }
 
 impl<'a> Allocations<'a> {
     fn new() -> Self {
         Self {
-            refdefs: HashMap::new(),
+            refdefs: Vec::new(),
+            refdef_ixs: HashMap::new(),
             links: Vec::with_capacity(128),
             cows: Vec::new(),
             alignments: Vec::new(),
+            code_infos: Vec::new(),
+            tags: Vec::new(),
         }
     }
 
@@ -1881,6 +2421,22 @@ impl<'a> Allocations<'a> {
         self.alignments.push(alignment);
         AlignmentIndex(ix)
     }
+
+    fn allocate_code_info(
+        &mut self,
+        language: CowStr<'a>,
+        attributes: CowStr<'a>,
+    ) -> CodeInfoIndex {
+        let ix = self.code_infos.len();
+        self.code_infos.push((language, attributes));
+        CodeInfoIndex(ix)
+    }
+
+    fn allocate_tag(&mut self, tag: Tag<'a>) -> TagIndex {
+        let ix = self.tags.len();
+        self.tags.push(tag);
+        TagIndex(ix)
+    }
 }
 
 impl<'a> Index<CowIndex> for Allocations<'a> {
@@ -1907,6 +2463,22 @@ impl<'a> Index<AlignmentIndex> for Allocations<'a> {
     }
 }
 
+impl<'a> Index<CodeInfoIndex> for Allocations<'a> {
+    type Output = (CowStr<'a>, CowStr<'a>);
+
+    fn index(&self, ix: CodeInfoIndex) -> &Self::Output {
+        self.code_infos.index(ix.0)
+    }
+}
+
+impl<'a> Index<TagIndex> for Allocations<'a> {
+    type Output = Tag<'a>;
+
+    fn index(&self, ix: TagIndex) -> &Self::Output {
+        self.tags.index(ix.0)
+    }
+}
+
 /// A struct containing information on the reachability of certain inline HTML
 /// elements. In particular, for cdata elements (`<![CDATA[`), processing
 /// elements (`<?`) and declarations (`<!DECLARATION`). The respectives usizes
@@ -1919,14 +2491,65 @@ pub(crate) struct HtmlScanGuard {
     pub declaration: usize,
 }
 
+/// Error returned by [`Parser::try_new`](struct.Parser.html#method.try_new)
+/// when the input exceeds the maximum length.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TooLarge {
+    /// Length of the input in bytes.
+    pub len: usize,
+    /// Maximum accepted length in bytes.
+    pub max_len: usize,
+}
+
+impl fmt::Display for TooLarge {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "input of {} bytes exceeds the maximum of {} bytes",
+            self.len, self.max_len
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for TooLarge {}
+
+/// An inline delimiter that wraps the text between runs of `c` in `tag`, when
+/// the runs are of equal length, at least `min_len` and at most `max_len`
+/// (unbounded when `None`).
+#[derive(Clone)]
+struct Delimiter<'a> {
+    c: u8,
+    min_len: usize,
+    max_len: Option<usize>,
+    tag: Tag<'a>,
+}
+
+/// Callback for references without a matching definition. It is passed the
+/// normalized and the raw reference label, and returns the url and title to
+/// link to, if any.
+pub type BrokenLinkCallback<'a> = &'a dyn Fn(&str, &str) -> Option<(String, String)>;
+
+/// Callback for reference links and images that stay unresolved. It is passed
+/// the reference label and the source range of the brackets around it.
+pub type UnresolvedReferenceCallback<'a> = &'a dyn Fn(&str, Range<usize>);
+
 /// Markdown event iterator.
 #[derive(Clone)]
 pub struct Parser<'a> {
     text: &'a str,
     tree: Tree<Item>,
     allocs: Allocations<'a>,
-    broken_link_callback: Option<&'a dyn Fn(&str, &str) -> Option<(String, String)>>,
+    broken_link_callback: Option<BrokenLinkCallback<'a>>,
+    unresolved_reference_callback: Option<UnresolvedReferenceCallback<'a>>,
+    shortcut_references: bool,
+    // delimiters resolved in the inline passes rather than by `parse_line`
+    delimiters: Vec<Delimiter<'a>>,
+    autolink_schemes: Option<&'a [&'a str]>,
+    autolink_emails: bool,
     html_scan_guard: HtmlScanGuard,
+    options: Options,
+    code_indent: usize,
 
     // used by inline passes. store them here for reuse
     inline_stack: InlineStack,
@@ -1952,23 +2575,205 @@ impl<'a> Parser<'a> {
     pub fn new_with_broken_link_callback(
         text: &'a str,
         options: Options,
-        broken_link_callback: Option<&'a dyn Fn(&str, &str) -> Option<(String, String)>>,
+        broken_link_callback: Option<BrokenLinkCallback<'a>>,
+    ) -> Parser<'a> {
+        Parser::new_internal(text, options, broken_link_callback, DEFAULT_CODE_INDENT)
+    }
+
+    /// Creates a new event iterator for a markdown string with given options,
+    /// where lines indented by at least `code_indent` spaces start an indented
+    /// code block, rather than the 4 spaces CommonMark specifies. The same indent
+    /// applies to code blocks inside list items. Indented code blocks can be
+    /// disabled altogether by passing `std::usize::MAX`.
+    ///
+    /// This is a constructor rather than a builder method because block
+    /// structure is parsed when the parser is created, so the indent has to be
+    /// known up front.
+    pub fn new_with_code_indent(text: &'a str, options: Options, code_indent: usize) -> Parser<'a> {
+        Parser::new_with_code_indent_and_broken_link_callback(text, options, code_indent, None)
+    }
+
+    /// Combines [`new_with_code_indent`](#method.new_with_code_indent) and
+    /// [`new_with_broken_link_callback`](#method.new_with_broken_link_callback).
+    pub fn new_with_code_indent_and_broken_link_callback(
+        text: &'a str,
+        options: Options,
+        code_indent: usize,
+        broken_link_callback: Option<BrokenLinkCallback<'a>>,
     ) -> Parser<'a> {
-        let first_pass = FirstPass::new(text, options);
+        Parser::new_internal(text, options, broken_link_callback, max(code_indent, 1))
+    }
+
+    /// Creates a new event iterator for a markdown string with given options,
+    /// unless the string is longer than `max_len` bytes. The length is checked
+    /// before any parsing is done.
+    pub fn try_new(
+        text: &'a str,
+        options: Options,
+        max_len: usize,
+    ) -> Result<Parser<'a>, TooLarge> {
+        if text.len() > max_len {
+            return Err(TooLarge {
+                len: text.len(),
+                max_len,
+            });
+        }
+        Ok(Parser::new_ext(text, options))
+    }
+
+    fn new_internal(
+        text: &'a str,
+        options: Options,
+        broken_link_callback: Option<BrokenLinkCallback<'a>>,
+        code_indent: usize,
+    ) -> Parser<'a> {
+        let first_pass = FirstPass::new(text, options, code_indent);
         let (mut tree, allocs) = first_pass.run();
         tree.reset();
         let inline_stack = Default::default();
         let link_stack = Default::default();
         let html_scan_guard = Default::default();
+        let autolink_schemes = if options.contains(Options::ENABLE_GFM_AUTOLINKS) {
+            Some(DEFAULT_AUTOLINK_SCHEMES)
+        } else {
+            None
+        };
+        let mut delimiters = Vec::new();
+        if options.contains(Options::ENABLE_HIGHLIGHT) {
+            delimiters.push(Delimiter {
+                c: b'=',
+                min_len: 2,
+                max_len: Some(2),
+                tag: Tag::Highlight,
+            });
+        }
         Parser {
             text,
             tree,
             allocs,
             broken_link_callback,
+            unresolved_reference_callback: None,
+            shortcut_references: true,
+            delimiters,
+            autolink_schemes,
+            autolink_emails: options.contains(Options::ENABLE_GFM_AUTOLINK_EMAIL_WITHOUT_MAILTO),
             inline_stack,
             link_stack,
             html_scan_guard,
+            options,
+            code_indent,
+        }
+    }
+
+    /// Sets the URL schemes that are turned into links when
+    /// `Options::ENABLE_GFM_AUTOLINKS` is set. Schemes are matched case
+    /// insensitively and must be followed by `://`. Defaults to `http` and
+    /// `https`. Has no effect when the option isn't set.
+    pub fn autolink_schemes(mut self, schemes: &'a [&'a str]) -> Self {
+        if self.autolink_schemes.is_some() {
+            self.autolink_schemes = Some(schemes);
         }
+        self
+    }
+
+    /// Sets a callback that is called for every reference link or image whose
+    /// label has no definition and isn't resolved by the broken link callback.
+    /// It is passed the label and the source range of the brackets around it,
+    /// which is useful for reporting typos in labels.
+    ///
+    /// Note that any bracketed text may be a shortcut reference, so text like
+    /// `[sic]` is reported too.
+    pub fn unresolved_reference_callback(
+        mut self,
+        callback: UnresolvedReferenceCallback<'a>,
+    ) -> Self {
+        self.unresolved_reference_callback = Some(callback);
+        self
+    }
+
+    /// Sets whether shortcut references like `[foo]` and collapsed references
+    /// like `[foo][]` are resolved. When disabled, only full references like
+    /// `[text][foo]` become links, so bracketed text is left alone. Footnote
+    /// references are not affected. Enabled by default, as required by the spec.
+    pub fn shortcut_references(mut self, enabled: bool) -> Self {
+        self.shortcut_references = enabled;
+        self
+    }
+
+    /// Registers an inline delimiter, so that text between runs of at least
+    /// `min_len` of the character `c` is wrapped in `tag`. Runs follow the
+    /// flanking rules of `_` emphasis, and only match runs of the same length,
+    /// like strikethrough. Registering a character again replaces its tag, and
+    /// registering `=` replaces the delimiter of `Options::ENABLE_HIGHLIGHT`.
+    ///
+    /// ```
+    /// use pulldown_cmark::{html, Parser, Tag};
+    ///
+    /// let parser = Parser::new("==marked==").register_delimiter('=', 2, Tag::Highlight);
+    /// let mut html = String::new();
+    /// html::push_html(&mut html, parser);
+    /// assert_eq!("<p><mark>marked</mark></p>\n", html);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `c` isn't ASCII punctuation, or is a character with an inline
+    /// meaning of its own, like `*` or `[`.
+    pub fn register_delimiter(mut self, c: char, min_len: usize, tag: Tag<'a>) -> Self {
+        assert!(
+            c.is_ascii_punctuation() && !special_bytes()[c as usize],
+            "cannot register {:?} as a delimiter",
+            c
+        );
+        let c = c as u8;
+        self.delimiters.retain(|delimiter| delimiter.c != c);
+        self.delimiters.push(Delimiter {
+            c,
+            min_len: max(min_len, 1),
+            max_len: None,
+            tag,
+        });
+        self
+    }
+
+    /// Returns the markdown source being parsed. The ranges produced by
+    /// [`into_offset_iter`](#method.into_offset_iter) index into this string.
+    pub fn source(&self) -> &'a str {
+        self.text
+    }
+
+    /// Returns the link reference definitions of the source in order, as their
+    /// label, destination, title and source range. The title is empty when the
+    /// definition has none, and the range leaves out trailing whitespace.
+    ///
+    /// Definitions of a label that was already defined are included too, even
+    /// though links only use the first one.
+    pub fn reference_definitions(
+        &self,
+    ) -> impl Iterator<Item = (&str, &str, &str, Range<usize>)> + '_ {
+        self.allocs.refdefs.iter().map(|(label, def, range)| {
+            let title = def.title.as_ref().map_or("", |title| title.as_ref());
+            (label.as_ref(), def.dest.as_ref(), title, range.clone())
+        })
+    }
+
+    /// Returns the number of events the parser has left to yield.
+    ///
+    /// This takes time proportional to the rest of the document: the full
+    /// inline parse runs on a copy of the parser, leaving this one untouched.
+    /// The copy may call the broken link callback, but doesn't report
+    /// unresolved references, so they're only reported once by this parser.
+    pub fn event_count(&self) -> usize {
+        let mut parser = self.clone();
+        parser.unresolved_reference_callback = None;
+        parser.count()
+    }
+
+    /// Whether text nodes may be split into links or delimiters, in which case
+    /// the inline content of a block is handled as soon as it's entered, rather
+    /// than at its first inline item.
+    fn splits_text(&self) -> bool {
+        self.autolink_schemes.is_some() || !self.delimiters.is_empty()
     }
 
     /// Handle inline markup.
@@ -1978,28 +2783,330 @@ impl<'a> Parser<'a> {
     ///
     /// Note: there's some potential for optimization here, but that's future work.
     fn handle_inline(&mut self) {
+        if !self.delimiters.is_empty() {
+            self.handle_custom_delimiters();
+        }
         self.handle_inline_pass1();
+        self.handle_math();
         self.handle_emphasis();
+        if let Some(schemes) = self.autolink_schemes {
+            self.handle_autolinks(self.tree.cur(), schemes);
+        }
     }
 
-    /// Handle inline HTML, code spans, and links.
-    ///
-    /// This function handles both inline HTML and code spans, because they have
-    /// the same precedence. It also handles links, even though they have lower
-    /// precedence, because the URL of links must not be processed.
-    fn handle_inline_pass1(&mut self) {
-        let mut code_delims = CodeDelims::new();
+    /// Resolves inline math between dollar signs at the current level. This runs
+    /// after code spans, inline HTML and links are resolved, so that math can't
+    /// break them up, and before emphasis, as math content is taken verbatim.
+    fn handle_math(&mut self) {
+        // node at which the last search for a closing dollar gave up, before
+        // which no other search can succeed either
+        let mut search_end = None;
         let mut cur = self.tree.cur();
-        let mut prev = TreePointer::Nil;
-
-        let block_end = self.tree[self.tree.peek_up().unwrap()].item.end;
-        let block_text = &self.text[..block_end];
-
-        while let TreePointer::Valid(mut cur_ix) = cur {
-            match self.tree[cur_ix].item.body {
-                ItemBody::MaybeHtml => {
-                    let next = self.tree[cur_ix].next;
-                    let autolink = if let TreePointer::Valid(next_ix) = next {
+        while let TreePointer::Valid(cur_ix) = cur {
+            if search_end == Some(cur) {
+                search_end = None;
+            }
+            if let ItemBody::MaybeMath(can_open, _) = self.tree[cur_ix].item.body {
+                self.tree[cur_ix].item.body = ItemBody::Text;
+                if can_open && search_end.is_none() {
+                    let mut scan = self.tree[cur_ix].next;
+                    while let TreePointer::Valid(scan_ix) = scan {
+                        match self.tree[scan_ix].item.body {
+                            ItemBody::MaybeMath(_, true) => {
+                                self.tree[cur_ix].item.body = ItemBody::InlineMath;
+                                self.tree[cur_ix].item.end = self.tree[scan_ix].item.end;
+                                self.tree[cur_ix].next = self.tree[scan_ix].next;
+                                break;
+                            }
+                            // inline math does not span multiple lines
+                            ItemBody::SoftBreak | ItemBody::HardBreak(_) => break,
+                            _ => scan = self.tree[scan_ix].next,
+                        }
+                    }
+                    if self.tree[cur_ix].item.body == ItemBody::Text {
+                        search_end = Some(scan);
+                    }
+                }
+            }
+            cur = self.tree[cur_ix].next;
+        }
+    }
+
+    /// Splits runs of registered delimiters out of the text nodes at the current
+    /// level, like `parse_line` does for the built-in delimiters, so that they
+    /// are resolved along with emphasis.
+    fn handle_custom_delimiters(&mut self) {
+        let bytes = self.text.as_bytes();
+        let mut prev = TreePointer::Nil;
+        let mut cur = self.tree.cur();
+        while let TreePointer::Valid(cur_ix) = cur {
+            let item = self.tree[cur_ix].item;
+            let next = self.tree[cur_ix].next;
+            if item.body != ItemBody::Text {
+                prev = cur;
+                cur = next;
+                continue;
+            }
+
+            let mut pieces = Vec::new();
+            let mut begin_text = item.start;
+            let mut ix = item.start;
+            // an escaped delimiter starts the text node after its backslash
+            let follows_prev = match prev {
+                TreePointer::Valid(prev_ix) => self.tree[prev_ix].item.end == ix,
+                TreePointer::Nil => false,
+            };
+            if ix > 0 && bytes[ix - 1] == b'\\' && !follows_prev {
+                ix += 1;
+            }
+            while ix < item.end {
+                let c = bytes[ix];
+                let (min_len, max_len) = match self.delimiters.iter().find(|d| d.c == c) {
+                    Some(delimiter) => (delimiter.min_len, delimiter.max_len),
+                    None => {
+                        ix += 1;
+                        continue;
+                    }
+                };
+                let count = 1 + scan_ch_repeat(&bytes[(ix + 1)..item.end], c);
+                let string_suffix = &self.text[ix..];
+                let can_open = delim_run_can_open(self.text, string_suffix, count, ix);
+                let can_close = delim_run_can_close(self.text, string_suffix, count, ix);
+                let within_max = match max_len {
+                    Some(max_len) => count <= max_len,
+                    None => true,
+                };
+                if min_len <= count && within_max && (can_open || can_close) {
+                    if begin_text < ix {
+                        pieces.push(Item {
+                            start: begin_text,
+                            end: ix,
+                            body: ItemBody::Text,
+                        });
+                    }
+                    for i in 0..count {
+                        pieces.push(Item {
+                            start: ix + i,
+                            end: ix + i + 1,
+                            body: ItemBody::MaybeEmphasis(count - i, can_open, can_close),
+                        });
+                    }
+                    begin_text = ix + count;
+                }
+                ix += count;
+            }
+            if pieces.is_empty() {
+                prev = cur;
+                cur = next;
+                continue;
+            }
+            if begin_text < item.end {
+                pieces.push(Item {
+                    start: begin_text,
+                    end: item.end,
+                    body: ItemBody::Text,
+                });
+            }
+
+            // the delimiters of a run must be consecutive nodes, so the text
+            // node is replaced rather than reused for the first piece
+            let first_ix = self.tree.create_node(pieces[0]);
+            let mut last_ix = first_ix;
+            for &piece in &pieces[1..] {
+                let piece_ix = self.tree.create_node(piece);
+                self.tree[last_ix].next = TreePointer::Valid(piece_ix);
+                last_ix = piece_ix;
+            }
+            self.tree[last_ix].next = next;
+            match prev {
+                TreePointer::Valid(prev_ix) => {
+                    self.tree[prev_ix].next = TreePointer::Valid(first_ix)
+                }
+                TreePointer::Nil => self.tree.replace_cur(first_ix),
+            }
+            prev = TreePointer::Valid(last_ix);
+            cur = next;
+        }
+    }
+
+    /// Resolves a wiki link, using the page name as link text unless display text
+    /// is given after a pipe.
+    fn make_wikilink(&mut self, wikilink_ix: TreeIndex) {
+        let text = self.text;
+        let Item { start, end, .. } = self.tree[wikilink_ix].item;
+        let content_start = start + 2;
+        let content_end = end - 2;
+        let bytes = &text.as_bytes()[..content_end];
+        let (page_end, display_start) = match text[content_start..content_end].find('|') {
+            Some(pipe) => (content_start + pipe, content_start + pipe + 1),
+            None => (content_end, content_start),
+        };
+        let page = text[content_start..page_end].trim();
+
+        let display_start =
+            display_start + scan_while(&bytes[display_start..], is_ascii_whitespace);
+        let display_end =
+            content_end - scan_rev_while(&bytes[display_start..], is_ascii_whitespace);
+        let (display_start, display_end) = if display_start < display_end {
+            (display_start, display_end)
+        } else {
+            let page_start =
+                content_start + scan_while(&bytes[content_start..], is_ascii_whitespace);
+            (page_start, page_start + page.len())
+        };
+
+        let (url, title) = match self
+            .broken_link_callback
+            .and_then(|callback| callback(page, page))
+        {
+            Some((url, title)) => (url.into(), title.into()),
+            None => (wikilink_destination(page), "".into()),
+        };
+        let link_ix = self.allocs.allocate_link(LinkType::WikiLink, url, title);
+        let text_ix = self.tree.create_node(Item {
+            start: display_start,
+            end: display_end,
+            body: ItemBody::Text,
+        });
+        self.tree[wikilink_ix].item.body = ItemBody::Link(link_ix);
+        self.tree[wikilink_ix].child = TreePointer::Valid(text_ix);
+    }
+
+    /// Turns bare URLs in the text of the chain starting at `cur` into links.
+    /// Descends into emphasis, but not into links and images.
+    fn handle_autolinks(&mut self, mut cur: TreePointer, schemes: &[&str]) {
+        while let TreePointer::Valid(cur_ix) = cur {
+            match self.tree[cur_ix].item.body {
+                ItemBody::Text => {
+                    cur = self.handle_autolinks_in_text(cur_ix, schemes);
+                    continue;
+                }
+                ItemBody::Emphasis
+                | ItemBody::Strong
+                | ItemBody::Strikethrough
+                | ItemBody::Superscript
+                | ItemBody::Subscript
+                | ItemBody::Spoiler
+                | ItemBody::CustomTag(_) => {
+                    let child = self.tree[cur_ix].child;
+                    self.handle_autolinks(child, schemes);
+                }
+                _ => (),
+            }
+            cur = self.tree[cur_ix].next;
+        }
+    }
+
+    /// Looks for the first bare URL or, if enabled, email address starting in
+    /// the given text node, which may extend into the text nodes directly
+    /// following it. Returns the node to continue from.
+    fn handle_autolinks_in_text(&mut self, text_ix: TreeIndex, schemes: &[&str]) -> TreePointer {
+        let bytes = self.text.as_bytes();
+        let Item { start, end, .. } = self.tree[text_ix].item;
+
+        // a link may span adjacent text nodes, such as those split at unmatched
+        // emphasis delimiters
+        let mut run_end = end;
+        let mut next = self.tree[text_ix].next;
+        while let TreePointer::Valid(next_ix) = next {
+            let item = self.tree[next_ix].item;
+            if item.body != ItemBody::Text || item.start != run_end {
+                break;
+            }
+            run_end = item.end;
+            next = self.tree[next_ix].next;
+        }
+
+        let found = (start..end)
+            .filter(|&ix| {
+                ix == 0 || is_ascii_whitespace(bytes[ix - 1]) || b"*_~(".contains(&bytes[ix - 1])
+            })
+            .filter_map(|ix| {
+                let data = &bytes[ix..run_end];
+                scan_extended_autolink(data, schemes)
+                    .map(|(len, is_www)| {
+                        let prefix = if is_www { "http://" } else { "" };
+                        (ix, ix + len, LinkType::Autolink, prefix)
+                    })
+                    .or_else(|| {
+                        if self.autolink_emails {
+                            scan_extended_email(data).map(|len| (ix, ix + len, LinkType::Email, ""))
+                        } else {
+                            None
+                        }
+                    })
+            })
+            .next();
+        let (link_start, link_end, link_type, prefix) = match found {
+            Some(found) => found,
+            None => return self.tree[text_ix].next,
+        };
+
+        // find the node containing the end of the link and split off the rest
+        let mut last_ix = text_ix;
+        while self.tree[last_ix].item.end < link_end {
+            last_ix = self.tree[last_ix].next.unwrap();
+        }
+        let mut after = self.tree[last_ix].next;
+        if link_end < self.tree[last_ix].item.end {
+            let rest_ix = self.tree.create_node(Item {
+                start: link_end,
+                end: self.tree[last_ix].item.end,
+                body: ItemBody::Text,
+            });
+            self.tree[rest_ix].next = after;
+            after = TreePointer::Valid(rest_ix);
+        }
+
+        let url = &self.text[link_start..link_end];
+        let url = if prefix.is_empty() {
+            url.into()
+        } else {
+            format!("{}{}", prefix, url).into()
+        };
+        let link_ix = self.allocs.allocate_link(link_type, url, "".into());
+        let link_text_ix = self.tree.create_node(Item {
+            start: link_start,
+            end: link_end,
+            body: ItemBody::Text,
+        });
+        let link = Item {
+            start: link_start,
+            end: link_end,
+            body: ItemBody::Link(link_ix),
+        };
+        let link_node_ix = if link_start > start {
+            let link_node_ix = self.tree.create_node(link);
+            self.tree[text_ix].item.end = link_start;
+            self.tree[text_ix].next = TreePointer::Valid(link_node_ix);
+            link_node_ix
+        } else {
+            self.tree[text_ix].item = link;
+            text_ix
+        };
+        self.tree[link_node_ix].child = TreePointer::Valid(link_text_ix);
+        self.tree[link_node_ix].next = after;
+        after
+    }
+
+    /// Handle inline HTML, code spans, and links.
+    ///
+    /// This function handles both inline HTML and code spans, because they have
+    /// the same precedence. It also handles links, even though they have lower
+    /// precedence, because the URL of links must not be processed.
+    fn handle_inline_pass1(&mut self) {
+        let mut code_delims = CodeDelims::new();
+        let mut cur = self.tree.cur();
+        let mut prev = TreePointer::Nil;
+
+        let block_end = self.tree[self.tree.peek_up().unwrap()].item.end;
+        let block_text = &self.text[..block_end];
+
+        while let TreePointer::Valid(mut cur_ix) = cur {
+            match self.tree[cur_ix].item.body {
+                ItemBody::MaybeHtml => {
+                    let next = self.tree[cur_ix].next;
+                    let autolink = if let TreePointer::Valid(next_ix) = next {
                         scan_autolink(block_text, self.tree[next_ix].item.start)
                     } else {
                         None
@@ -2108,12 +3215,50 @@ impl<'a> Parser<'a> {
                         ty: LinkStackTy::Image,
                     });
                 }
+                ItemBody::MaybeWikiLink => {
+                    self.tree[cur_ix].item.body = ItemBody::Text;
+                    self.link_stack.push(LinkStackEl {
+                        node: cur_ix,
+                        ty: LinkStackTy::WikiLink,
+                    });
+                }
                 ItemBody::MaybeLinkClose => {
                     if let Some(tos) = self.link_stack.pop() {
                         if tos.ty == LinkStackTy::Disabled {
                             self.tree[cur_ix].item.body = ItemBody::Text;
                             continue;
                         }
+                        if tos.ty == LinkStackTy::WikiLink {
+                            // the wiki link only resolves if nothing, like a code
+                            // span, has swallowed its closing brackets
+                            let start = self.tree[tos.node].item.start;
+                            let end = scan_wikilink(&block_text.as_bytes()[start..])
+                                .map_or(0, |n| start + n);
+                            let next = self.tree[cur_ix].next;
+                            let closing_ix = match next {
+                                TreePointer::Valid(next_ix)
+                                    if self.tree[cur_ix].item.end + 1 == end
+                                        && self.tree[next_ix].item.body
+                                            == ItemBody::MaybeLinkClose =>
+                                {
+                                    Some(next_ix)
+                                }
+                                _ => None,
+                            };
+                            if let Some(closing_ix) = closing_ix {
+                                self.tree[tos.node].item.end = end;
+                                self.tree[tos.node].next = self.tree[closing_ix].next;
+                                self.make_wikilink(tos.node);
+                                self.link_stack.disable_all_links();
+                                cur = TreePointer::Valid(tos.node);
+                                cur_ix = tos.node;
+                            } else {
+                                self.tree[cur_ix].item.body = ItemBody::Text;
+                            }
+                            prev = cur;
+                            cur = self.tree[cur_ix].next;
+                            continue;
+                        }
                         let next = self.tree[cur_ix].next;
                         if let Some((next_ix, url, title)) =
                             self.scan_inline_link(block_text, self.tree[cur_ix].item.end, next)
@@ -2145,10 +3290,10 @@ impl<'a> Parser<'a> {
                             // ok, so its not an inline link. maybe it is a reference
                             // to a defined link?
                             let scan_result = scan_reference(&self.tree, block_text, next);
-                            let node_after_link = match scan_result {
-                                RefScan::LinkLabel(_, next_node) => next_node,
-                                RefScan::Collapsed(next_node) => next_node,
-                                RefScan::Failed => next,
+                            let (node_after_link, link_end) = match scan_result {
+                                RefScan::LinkLabel(_, next_node, end) => (next_node, end),
+                                RefScan::Collapsed(next_node, end) => (next_node, end),
+                                RefScan::Failed => (next, self.tree[cur_ix].item.end),
                             };
                             let link_type = match &scan_result {
                                 RefScan::LinkLabel(..) => LinkType::Reference,
@@ -2169,6 +3314,16 @@ impl<'a> Parser<'a> {
                                 }
                             };
 
+                            let label = match label {
+                                Some(ReferenceLabel::Link(_))
+                                    if link_type != LinkType::Reference
+                                        && !self.shortcut_references =>
+                                {
+                                    None
+                                }
+                                label => label,
+                            };
+
                             // see if it's a footnote reference
                             if let Some(ReferenceLabel::Footnote(l)) = label {
                                 self.tree[tos.node].next = node_after_link;
@@ -2182,10 +3337,11 @@ impl<'a> Parser<'a> {
                             } else if let Some(ReferenceLabel::Link(link_label)) = label {
                                 let type_url_title = self
                                     .allocs
-                                    .refdefs
+                                    .refdef_ixs
                                     .get(&UniCase::new(link_label.as_ref().into()))
-                                    .map(|matching_def| {
+                                    .map(|&def_ix| {
                                         // found a matching definition!
+                                        let matching_def = &self.allocs.refdefs[def_ix].1;
                                         let title = matching_def
                                             .title
                                             .as_ref()
@@ -2215,6 +3371,7 @@ impl<'a> Parser<'a> {
                                     } else {
                                         ItemBody::Link(link_ix)
                                     };
+                                    self.tree[tos.node].item.end = link_end;
                                     let label_node = self.tree[tos.node].next;
 
                                     // lets do some tree surgery to add the link to the tree
@@ -2239,6 +3396,17 @@ impl<'a> Parser<'a> {
                                         self.link_stack.disable_all_links();
                                     }
                                 } else {
+                                    // the label of a full reference is retried as a
+                                    // shortcut reference, so report it only then
+                                    let callback = match link_type {
+                                        LinkType::Reference => None,
+                                        _ => self.unresolved_reference_callback,
+                                    };
+                                    if let Some(callback) = callback {
+                                        let range = self.tree[tos.node].item.start
+                                            ..self.tree[cur_ix].item.end;
+                                        callback(link_label.as_ref(), range);
+                                    }
                                     self.tree[cur_ix].item.body = ItemBody::Text;
                                 }
                             } else {
@@ -2282,8 +3450,19 @@ impl<'a> Parser<'a> {
 
                         // work from the inside out
                         while start > el.start + el.count - match_count {
-                            let (inc, ty) = if c == b'~' {
+                            let (inc, ty) = if c == b'~' && match_count == 2 {
                                 (2, ItemBody::Strikethrough)
+                            } else if c == b'~' {
+                                (1, ItemBody::Subscript)
+                            } else if c == b'^' {
+                                (1, ItemBody::Superscript)
+                            } else if c == b'|' {
+                                (2, ItemBody::Spoiler)
+                            } else if let Some(delimiter) =
+                                self.delimiters.iter().find(|d| d.c == c)
+                            {
+                                let tag_ix = self.allocs.allocate_tag(delimiter.tag.clone());
+                                (match_count, ItemBody::CustomTag(tag_ix))
                             } else if start > el.start + el.count - match_count + 1 {
                                 (2, ItemBody::Strong)
                             } else {
@@ -2294,7 +3473,7 @@ impl<'a> Parser<'a> {
                             end = end + inc;
                             self.tree[root].item.body = ty;
                             self.tree[root].item.end = self.tree[end].item.end;
-                            self.tree[root].child = TreePointer::Valid(start);
+                            self.tree[root].child = self.tree[start - 1].next;
                             self.tree[root].next = TreePointer::Nil;
                             start = root;
                         }
@@ -2402,7 +3581,7 @@ impl<'a> Parser<'a> {
             let c = bytes[i];
 
             if c == close {
-                let cow = if mark == 1 {
+                let cow = if mark == start_ix + 1 {
                     (i - start_ix + 1, text[mark..i].into())
                 } else {
                     title.push_str(&text[mark..i]);
@@ -2480,7 +3659,7 @@ impl<'a> Parser<'a> {
             let mut ix = first_ix;
 
             while ix < close {
-                if let ItemBody::HardBreak | ItemBody::SoftBreak = self.tree[ix].item.body {
+                if let ItemBody::HardBreak(_) | ItemBody::SoftBreak = self.tree[ix].item.body {
                     if drop_enclosing_whitespace {
                         // check whether break should be ignored
                         if ix == first_ix {
@@ -2547,7 +3726,7 @@ impl<'a> Parser<'a> {
         } else {
             let i = scan_html_block_inner(
                 &bytes[ix..],
-                Some(&|_bytes| {
+                Some(&|bytes| {
                     let mut line_start = LineStart::new(bytes);
                     let _ = scan_containers(&self.tree, &mut line_start);
                     line_start.bytes_scanned()
@@ -2557,11 +3736,194 @@ impl<'a> Parser<'a> {
         }
     }
 
+    /// Updates the parser after the `edit` range of its source has been replaced,
+    /// `text` being the whole new source, and restarts iteration at its beginning.
+    ///
+    /// Only the top-level blocks around the edit are parsed again and the blocks
+    /// after them are kept, with their offsets shifted. Since an edited line may
+    /// continue the block before it, parsing starts at the block preceding the
+    /// first changed block, or earlier, at a block that follows a blank line. It
+    /// stops at the first block after the edit that follows an unchanged blank
+    /// line, once all new blocks are closed. The whole source is parsed again when
+    /// the edit reaches into the first block or changes the front matter, or when
+    /// the old or new source has link reference definitions, as those apply to
+    /// links in every block.
+    ///
+    /// The nodes of replaced blocks are not reclaimed, so memory use grows with
+    /// every edit until the source is parsed anew with one of the constructors.
+    ///
+    /// # Panics
+    ///
+    /// Panics when `edit` is out of bounds of the previous source, or when `text`
+    /// is shorter than the part of the previous source outside of `edit`.
+    pub fn reparse(&mut self, edit: Range<usize>, text: &'a str) {
+        assert!(edit.start <= edit.end && edit.end <= self.text.len());
+        let kept_len = self.text.len() - edit.len();
+        assert!(text.len() >= kept_len);
+        let new_end = edit.start + text.len() - kept_len;
+
+        let front_matter_len = |text: &str| {
+            if self.options.contains(Options::ENABLE_FRONT_MATTER) {
+                scan_front_matter(text.as_bytes()).map(|(_, len)| len)
+            } else {
+                None
+            }
+        };
+        let partial = self.allocs.refdefs.is_empty()
+            && front_matter_len(self.text) == front_matter_len(text)
+            && self.reparse_blocks(edit, new_end, text);
+        if !partial {
+            let first_pass = FirstPass::new(text, self.options, self.code_indent);
+            let (tree, allocs) = first_pass.run();
+            self.tree = tree;
+            self.allocs = allocs;
+        }
+        self.text = text;
+        self.tree.reset();
+        self.html_scan_guard = Default::default();
+    }
+
+    /// Replaces the top-level blocks affected by an edit of the source, where
+    /// `edit` is the replaced range of the old source and `new_end` the end of
+    /// its replacement in `text`. Returns false when the blocks cannot be
+    /// replaced separately, leaving the tree in an unspecified state.
+    fn reparse_blocks(&mut self, edit: Range<usize>, new_end: usize, text: &'a str) -> bool {
+        let bytes = self.text.as_bytes();
+        self.tree.reset();
+        let mut blocks = Vec::new();
+        let mut cur = self.tree.cur();
+        while let TreePointer::Valid(cur_ix) = cur {
+            blocks.push(cur_ix);
+            cur = self.tree[cur_ix].next;
+        }
+
+        // Blocks can be parsed separately from the blocks before them when they
+        // follow a blank line that isn't part of another block, like the blank
+        // lines inside HTML blocks. Returns the start of the block's first line
+        // and of the blank line.
+        let tree = &self.tree;
+        let boundary = |pos: usize| {
+            let line_start = block_line_start(bytes, tree[blocks[pos]].item.start);
+            let blank_start = blank_line_before(bytes, line_start)?;
+            let prev = tree[blocks[pos - 1]].item;
+            let in_prev = match prev.body {
+                // lists end after their trailing blank lines
+                ItemBody::List(..) => false,
+                _ => prev.end > blank_start,
+            };
+            if in_prev {
+                None
+            } else {
+                Some((line_start, blank_start))
+            }
+        };
+
+        // The block before the first one touched by the edit, which may continue
+        // into it, is the earliest block that can change.
+        let first_changed = blocks
+            .iter()
+            .position(|&ix| tree[ix].item.end >= edit.start)
+            .unwrap_or(blocks.len());
+        let region = match (1..first_changed)
+            .rev()
+            .find(|&pos| boundary(pos).is_some())
+        {
+            Some(pos) => pos,
+            None => return false,
+        };
+        let region_start = boundary(region).unwrap().0;
+
+        // Block boundaries after the edit, where the blank line is unchanged.
+        let resume_points: Vec<_> = (max(first_changed, 1)..blocks.len())
+            .filter_map(|pos| match boundary(pos) {
+                Some((line_start, blank_start)) if blank_start >= edit.end => {
+                    Some((line_start, blocks[pos]))
+                }
+                _ => None,
+            })
+            .collect();
+
+        let mut tree = core::mem::replace(&mut self.tree, Tree::with_capacity(0));
+        tree.restore_cursor((TreePointer::Valid(blocks[region - 1]), Vec::new()));
+        let allocs = core::mem::replace(&mut self.allocs, Allocations::new());
+        let mut first_pass =
+            FirstPass::with_tree(text, self.options, self.code_indent, tree, allocs);
+        let mut resume_points = resume_points.into_iter().peekable();
+        let mut tail = TreePointer::Nil;
+        first_pass.run_region(region_start, |ix| {
+            if ix < new_end {
+                return false;
+            }
+            let old_ix = ix - new_end + edit.end;
+            while let Some(&(line_start, block_ix)) = resume_points.peek() {
+                if line_start > old_ix {
+                    break;
+                }
+                resume_points.next();
+                if line_start == old_ix {
+                    tail = TreePointer::Valid(block_ix);
+                    return true;
+                }
+            }
+            false
+        });
+        if !first_pass.allocs.refdefs.is_empty() {
+            return false;
+        }
+
+        let mut tree = first_pass.tree;
+        let last_ix = tree.cur().unwrap();
+        tree[last_ix].next = tail;
+        let mut stack = vec![tail];
+        while let Some(node) = stack.pop() {
+            if let TreePointer::Valid(node_ix) = node {
+                let item = &mut tree[node_ix].item;
+                item.start = item.start + new_end - edit.end;
+                item.end = item.end + new_end - edit.end;
+                stack.push(tree[node_ix].next);
+                stack.push(tree[node_ix].child);
+            }
+        }
+        self.tree = tree;
+        self.allocs = first_pass.allocs;
+        true
+    }
+
     /// Consumes the event iterator and produces an iterator that produces
     /// `(Event, Range)` pairs, where the `Range` value maps to the corresponding
-    /// range in the markdown source.
+    /// range in the markdown source. See [`OffsetIter`](struct.OffsetIter.html)
+    /// for the ranges reported for `Start` and `End` events.
     pub fn into_offset_iter(self) -> OffsetIter<'a> {
-        OffsetIter { inner: self }
+        OffsetIter {
+            inner: self,
+            last_end: 0,
+        }
+    }
+
+    /// Consumes the event iterator and produces an iterator that produces
+    /// `(usize, Event)` pairs, where the `usize` value is the number of open
+    /// `Start` tags enclosing the event.
+    pub fn with_depth(self) -> DepthIter<'a> {
+        DepthIter {
+            inner: self,
+            depth: 0,
+        }
+    }
+
+    /// Consumes the event iterator and produces an iterator that leaves out
+    /// all `Html` events, both from HTML blocks and inline HTML.
+    pub fn filter_html(self) -> FilterHtml<'a> {
+        FilterHtml { inner: self }
+    }
+
+    /// Consumes the event iterator and produces an iterator that passes each
+    /// event through `visitor` before yielding it. See
+    /// [`Visitor`](trait.Visitor.html).
+    pub fn visit<V: Visitor<'a>>(self, visitor: V) -> Visit<'a, V> {
+        Visit {
+            inner: self,
+            visitor,
+        }
     }
 }
 
@@ -2574,7 +3936,7 @@ pub(crate) enum LoopInstruction<T> {
 
 /// This function walks the byte slices from the given index and
 /// calls the callback function on all bytes (and their indices) that are in the following set:
-/// `` ` ``, `\`, `&`, `*`, `_`, `~`, `!`, `<`, `[`, `]`, `|`, `\r`, `\n`
+/// `` ` ``, `\`, `&`, `*`, `_`, `~`, `!`, `<`, `[`, `]`, `|`, `$`, `\r`, `\n`
 /// It is guaranteed not call the callback on other bytes.
 /// Whenever `callback(ix, byte)` returns a `ContinueAndSkip(n)` value, the callback
 /// will not be called with an index that is less than `ix + n + 1`.
@@ -2602,6 +3964,7 @@ const fn special_bytes() -> [bool; 256] {
     bytes[b'!' as usize] = true;
     bytes[b'[' as usize] = true;
     bytes[b'~' as usize] = true;
+    bytes[b'^' as usize] = true;
     bytes[b'`' as usize] = true;
     bytes[b'|' as usize] = true;
     bytes[b'\\' as usize] = true;
@@ -2611,6 +3974,7 @@ const fn special_bytes() -> [bool; 256] {
     bytes[b'\n' as usize] = true;
     bytes[b']' as usize] = true;
     bytes[b'&' as usize] = true;
+    bytes[b'$' as usize] = true;
     bytes
 }
 
@@ -2647,10 +4011,21 @@ where
 /// Generates tuples where the first element is the markdown event and the second
 /// is a the corresponding range in the source string.
 ///
+/// Events that do not contain other elements map to their full source range. For
+/// `Start` events, the range covers the opening syntax of the element, from its
+/// start up to its first child (e.g. the opening fence line of a fenced code block
+/// or the `[` of a link). For `End` events, the range covers the closing syntax,
+/// from the end of its last child up to the end of the element (e.g. the closing
+/// fence of a fenced code block or the `](url)` of a link). These ranges are empty
+/// for elements without such syntax, like paragraphs. Elements without any
+/// children report their full source range for both events.
+///
 /// Constructed from a `Parser` using its
 /// [`into_offset_iter`](struct.Parser.html#method.into_offset_iter) method.
 pub struct OffsetIter<'a> {
     inner: Parser<'a>,
+    // end offset of the most recently visited node
+    last_end: usize,
 }
 
 impl<'a> Iterator for OffsetIter<'a> {
@@ -2660,12 +4035,16 @@ impl<'a> Iterator for OffsetIter<'a> {
         match self.inner.tree.cur() {
             TreePointer::Nil => {
                 let ix = self.inner.tree.pop()?;
-                let tag = item_to_tag(&self.inner.tree[ix].item, &self.inner.allocs);
+                let node = self.inner.tree[ix];
+                let tag = item_to_tag(&node.item, &self.inner.allocs);
                 self.inner.tree.next_sibling(ix);
-                Some((
-                    Event::End(tag),
-                    self.inner.tree[ix].item.start..self.inner.tree[ix].item.end,
-                ))
+                let start = if node.child == TreePointer::Nil {
+                    node.item.start
+                } else {
+                    min(max(self.last_end, node.item.start), node.item.end)
+                };
+                self.last_end = node.item.end;
+                Some((Event::End(tag), start..node.item.end))
             }
             TreePointer::Valid(cur_ix) => {
                 if self.inner.tree[cur_ix].item.body.is_inline() {
@@ -2675,73 +4054,320 @@ impl<'a> Iterator for OffsetIter<'a> {
                 let node = self.inner.tree[cur_ix];
                 let item = node.item;
                 let event = item_to_event(item, self.inner.text, &self.inner.allocs);
-                if let Event::Start(..) = event {
+                let end = if let Event::Start(..) = event {
                     self.inner.tree.push();
+                    if self.inner.splits_text()
+                        && item.body.has_inline_content()
+                        && self.inner.tree.cur() != TreePointer::Nil
+                    {
+                        self.inner.handle_inline();
+                    }
+                    if let TreePointer::Valid(child_ix) = self.inner.tree[cur_ix].child {
+                        let child = self.inner.tree[child_ix].item;
+                        let mut child_start = child.start;
+                        // A leading backslash escape belongs to the text, not to
+                        // the opening syntax.
+                        if let ItemBody::Text = child.body {
+                            if child_start > item.start
+                                && self.inner.text.as_bytes()[child_start - 1] == b'\\'
+                            {
+                                child_start -= 1;
+                            }
+                        }
+                        min(max(child_start, item.start), item.end)
+                    } else {
+                        item.end
+                    }
                 } else {
                     self.inner.tree.next_sibling(cur_ix);
-                }
-                Some((event, item.start..item.end))
+                    item.end
+                };
+                self.last_end = end;
+                Some((event, item.start..end))
             }
         }
     }
 }
 
-fn item_to_tag<'a>(item: &Item, allocs: &Allocations<'a>) -> Tag<'a> {
-    match item.body {
-        ItemBody::Paragraph => Tag::Paragraph,
-        ItemBody::Emphasis => Tag::Emphasis,
-        ItemBody::Strong => Tag::Strong,
-        ItemBody::Strikethrough => Tag::Strikethrough,
-        ItemBody::Link(link_ix) => {
-            let &(ref link_type, ref url, ref title) = allocs.index(link_ix);
-            Tag::Link(*link_type, url.clone(), title.clone())
-        }
-        ItemBody::Image(link_ix) => {
-            let &(ref link_type, ref url, ref title) = allocs.index(link_ix);
-            Tag::Image(*link_type, url.clone(), title.clone())
+/// Parses `text` with the given options into a vector of events that own their
+/// strings, so that they don't borrow from `text`.
+///
+/// ```
+/// use pulldown_cmark::{parse_to_events, Event, Options, Tag};
+///
+/// let events = {
+///     let text = String::from("*hi*");
+///     parse_to_events(&text, Options::empty())
+/// };
+/// assert_eq!(
+///     vec![
+///         Event::Start(Tag::Paragraph),
+///         Event::Start(Tag::Emphasis),
+///         Event::Text("hi".into()),
+///         Event::End(Tag::Emphasis),
+///         Event::End(Tag::Paragraph),
+///     ],
+///     events
+/// );
+/// ```
+pub fn parse_to_events(text: &str, options: Options) -> Vec<Event<'static>> {
+    Parser::new_ext(text, options)
+        .map(Event::into_static)
+        .collect()
+}
+
+/// Converts a byte offset into `text`, such as those reported by
+/// [`OffsetIter`](struct.OffsetIter.html), into a 1-based line and column.
+///
+/// Columns count characters, with tabs advancing to the next tab stop at a
+/// multiple of 4 columns, as CommonMark does for indentation.
+///
+/// # Panics
+///
+/// Panics when `offset` is out of bounds or not on a character boundary.
+pub fn line_column(text: &str, offset: usize) -> (usize, usize) {
+    let bytes = text.as_bytes();
+    let mut line = 1;
+    let mut line_start = 0;
+    for (ix, &b) in bytes[..offset].iter().enumerate() {
+        if b == b'\n' || b == b'\r' && bytes.get(ix + 1) != Some(&b'\n') {
+            line += 1;
+            line_start = ix + 1;
         }
-        ItemBody::Heading(level) => Tag::Heading(level),
-        ItemBody::FencedCodeBlock(cow_ix) => {
-            Tag::CodeBlock(CodeBlockKind::Fenced(allocs[cow_ix].clone()))
+    }
+    let column = text[line_start..offset].chars().fold(0, |column, c| {
+        if c == '\t' {
+            column / 4 * 4 + 4
+        } else {
+            column + 1
         }
-        ItemBody::IndentCodeBlock => Tag::CodeBlock(CodeBlockKind::Indented),
-        ItemBody::BlockQuote => Tag::BlockQuote,
-        ItemBody::List(_, c, listitem_start) => {
-            if c == b'.' || c == b')' {
-                Tag::List(Some(listitem_start))
-            } else {
-                Tag::List(None)
+    });
+    (line, column + 1)
+}
+
+/// Markdown event and nesting depth iterator.
+///
+/// Generates tuples where the first element is the nesting depth of the event and
+/// the second is the markdown event. A `Start` event is reported at the depth of
+/// its parent, as is its matching `End` event.
+///
+/// Constructed from a `Parser` using its
+/// [`with_depth`](struct.Parser.html#method.with_depth) method.
+pub struct DepthIter<'a> {
+    inner: Parser<'a>,
+    depth: usize,
+}
+
+impl<'a> Iterator for DepthIter<'a> {
+    type Item = (usize, Event<'a>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let event = self.inner.next()?;
+        let depth = match event {
+            Event::Start(..) => {
+                self.depth += 1;
+                self.depth - 1
             }
-        }
-        ItemBody::ListItem(_) => Tag::Item,
-        ItemBody::TableHead => Tag::TableHead,
-        ItemBody::TableCell => Tag::TableCell,
-        ItemBody::TableRow => Tag::TableRow,
-        ItemBody::Table(alignment_ix) => Tag::Table(allocs[alignment_ix].clone()),
-        ItemBody::FootnoteDefinition(cow_ix) => Tag::FootnoteDefinition(allocs[cow_ix].clone()),
-        _ => panic!("unexpected item body {:?}", item.body),
+            Event::End(..) => {
+                self.depth -= 1;
+                self.depth
+            }
+            _ => self.depth,
+        };
+        Some((depth, event))
     }
 }
 
-fn item_to_event<'a>(item: Item, text: &'a str, allocs: &Allocations<'a>) -> Event<'a> {
-    let tag = match item.body {
-        ItemBody::Text => return Event::Text(text[item.start..item.end].into()),
-        ItemBody::Code(cow_ix) => return Event::Code(allocs[cow_ix].clone()),
-        ItemBody::SynthesizeText(cow_ix) => return Event::Text(allocs[cow_ix].clone()),
-        ItemBody::Html => return Event::Html(text[item.start..item.end].into()),
-        ItemBody::SoftBreak => return Event::SoftBreak,
-        ItemBody::HardBreak => return Event::HardBreak,
-        ItemBody::FootnoteReference(cow_ix) => {
-            return Event::FootnoteReference(allocs[cow_ix].clone())
-        }
-        ItemBody::TaskListMarker(checked) => return Event::TaskListMarker(checked),
-        ItemBody::Rule => return Event::Rule,
+/// Markdown event iterator without raw HTML.
+///
+/// HTML blocks are made up of `Html` events only, so dropping those removes
+/// them entirely. Text inside inline HTML elements, like `<b>bold</b>`, is kept.
+///
+/// Constructed from a `Parser` using its
+/// [`filter_html`](struct.Parser.html#method.filter_html) method.
+pub struct FilterHtml<'a> {
+    inner: Parser<'a>,
+}
 
-        ItemBody::Paragraph => Tag::Paragraph,
-        ItemBody::Emphasis => Tag::Emphasis,
-        ItemBody::Strong => Tag::Strong,
-        ItemBody::Strikethrough => Tag::Strikethrough,
-        ItemBody::Link(link_ix) => {
+impl<'a> Iterator for FilterHtml<'a> {
+    type Item = Event<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.inner.next()? {
+                Event::Html(_) => (),
+                event => return Some(event),
+            }
+        }
+    }
+}
+
+/// Rewrites events as they are parsed, with hooks for the events and tags that
+/// are commonly changed.
+///
+/// Every method has a default implementation that leaves its input unchanged
+/// apart from calling the more specific hooks, so implementors only override the
+/// ones they need. `event` is called for every event and passes the tags of
+/// `Start` and `End` events to `tag`, which passes links, images and code blocks
+/// on to their own hooks. As both the `Start` and `End` event of an element carry
+/// its tag, the hooks for a tag are called twice per element.
+pub trait Visitor<'a> {
+    fn event(&mut self, event: Event<'a>) -> Event<'a> {
+        match event {
+            Event::Start(tag) => Event::Start(self.tag(tag)),
+            Event::End(tag) => Event::End(self.tag(tag)),
+            Event::Text(text) => Event::Text(self.text(text)),
+            event => event,
+        }
+    }
+
+    fn tag(&mut self, tag: Tag<'a>) -> Tag<'a> {
+        match tag {
+            Tag::Link(link_type, dest, title) => {
+                let (dest, title) = self.link(link_type, dest, title);
+                Tag::Link(link_type, dest, title)
+            }
+            Tag::Image(link_type, dest, title) => {
+                let (dest, title) = self.image(link_type, dest, title);
+                Tag::Image(link_type, dest, title)
+            }
+            Tag::CodeBlock(kind) => Tag::CodeBlock(self.code_block(kind)),
+            tag => tag,
+        }
+    }
+
+    /// Returns the destination and title of a link.
+    fn link(
+        &mut self,
+        _link_type: LinkType,
+        dest: CowStr<'a>,
+        title: CowStr<'a>,
+    ) -> (CowStr<'a>, CowStr<'a>) {
+        (dest, title)
+    }
+
+    /// Returns the source and title of an image.
+    fn image(
+        &mut self,
+        _link_type: LinkType,
+        dest: CowStr<'a>,
+        title: CowStr<'a>,
+    ) -> (CowStr<'a>, CowStr<'a>) {
+        (dest, title)
+    }
+
+    fn code_block(&mut self, kind: CodeBlockKind<'a>) -> CodeBlockKind<'a> {
+        kind
+    }
+
+    fn text(&mut self, text: CowStr<'a>) -> CowStr<'a> {
+        text
+    }
+}
+
+/// Markdown event iterator that rewrites events with a
+/// [`Visitor`](trait.Visitor.html).
+///
+/// Constructed from a `Parser` using its
+/// [`visit`](struct.Parser.html#method.visit) method.
+pub struct Visit<'a, V> {
+    inner: Parser<'a>,
+    visitor: V,
+}
+
+impl<'a, V: Visitor<'a>> Iterator for Visit<'a, V> {
+    type Item = Event<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let event = self.inner.next()?;
+        Some(self.visitor.event(event))
+    }
+}
+
+fn item_to_tag<'a>(item: &Item, allocs: &Allocations<'a>) -> Tag<'a> {
+    match item.body {
+        ItemBody::Paragraph => Tag::Paragraph,
+        ItemBody::Emphasis => Tag::Emphasis,
+        ItemBody::Strong => Tag::Strong,
+        ItemBody::Strikethrough => Tag::Strikethrough,
+        ItemBody::Superscript => Tag::Superscript,
+        ItemBody::Subscript => Tag::Subscript,
+        ItemBody::Spoiler => Tag::Spoiler,
+        ItemBody::CustomTag(tag_ix) => allocs[tag_ix].clone(),
+        ItemBody::Link(link_ix) => {
+            let &(ref link_type, ref url, ref title) = allocs.index(link_ix);
+            Tag::Link(*link_type, url.clone(), title.clone())
+        }
+        ItemBody::Image(link_ix) => {
+            let &(ref link_type, ref url, ref title) = allocs.index(link_ix);
+            Tag::Image(*link_type, url.clone(), title.clone())
+        }
+        ItemBody::Heading(level) => Tag::Heading(level),
+        ItemBody::FencedCodeBlock(info_ix) => {
+            let (language, attributes) = &allocs[info_ix];
+            Tag::CodeBlock(CodeBlockKind::Fenced(language.clone(), attributes.clone()))
+        }
+        ItemBody::IndentCodeBlock => Tag::CodeBlock(CodeBlockKind::Indented),
+        ItemBody::BlockQuote => Tag::BlockQuote,
+        ItemBody::Admonition(kind) => Tag::Admonition(kind),
+        ItemBody::List(_, c, listitem_start) => {
+            if c == b'.' || c == b')' {
+                Tag::List(Some(listitem_start), char::from(c))
+            } else {
+                Tag::List(None, char::from(c))
+            }
+        }
+        ItemBody::ListItem(_) => Tag::Item,
+        ItemBody::TableHead => Tag::TableHead,
+        ItemBody::TableCell => Tag::TableCell,
+        ItemBody::TableRow => Tag::TableRow,
+        ItemBody::Table(alignment_ix) => Tag::Table(allocs[alignment_ix].clone()),
+        ItemBody::FootnoteDefinition(cow_ix) => Tag::FootnoteDefinition(allocs[cow_ix].clone()),
+        _ => panic!("unexpected item body {:?}", item.body),
+    }
+}
+
+/// Derives the destination of a wiki link from its page name: lowercased, with
+/// whitespace replaced by hyphens.
+fn wikilink_destination(page: &str) -> CowStr<'static> {
+    page.split_whitespace()
+        .map(|word| word.to_lowercase())
+        .collect::<Vec<_>>()
+        .join("-")
+        .into()
+}
+
+fn item_to_event<'a>(item: Item, text: &'a str, allocs: &Allocations<'a>) -> Event<'a> {
+    let tag = match item.body {
+        ItemBody::Text => return Event::Text(text[item.start..item.end].into()),
+        ItemBody::Code(cow_ix) => return Event::Code(allocs[cow_ix].clone()),
+        ItemBody::SynthesizeText(cow_ix) => return Event::Text(allocs[cow_ix].clone()),
+        ItemBody::Html => return Event::Html(text[item.start..item.end].into()),
+        ItemBody::SoftBreak => return Event::SoftBreak,
+        ItemBody::HardBreak(style) => return Event::HardBreak(style),
+        ItemBody::FootnoteReference(cow_ix) => {
+            return Event::FootnoteReference(allocs[cow_ix].clone())
+        }
+        ItemBody::TaskListMarker(checked) => return Event::TaskListMarker(checked),
+        ItemBody::Rule => return Event::Rule,
+        ItemBody::InlineMath => {
+            return Event::InlineMath(text[(item.start + 1)..(item.end - 1)].into())
+        }
+        ItemBody::DisplayMath(cow_ix) => return Event::DisplayMath(allocs[cow_ix].clone()),
+        ItemBody::ReferenceDefinition => {
+            return Event::ReferenceDefinition(text[item.start..item.end].into())
+        }
+        ItemBody::FrontMatter => return Event::FrontMatter(text[item.start..item.end].into()),
+
+        ItemBody::Paragraph => Tag::Paragraph,
+        ItemBody::Emphasis => Tag::Emphasis,
+        ItemBody::Strong => Tag::Strong,
+        ItemBody::Strikethrough => Tag::Strikethrough,
+        ItemBody::Superscript => Tag::Superscript,
+        ItemBody::Subscript => Tag::Subscript,
+        ItemBody::Spoiler => Tag::Spoiler,
+        ItemBody::CustomTag(tag_ix) => allocs[tag_ix].clone(),
+        ItemBody::Link(link_ix) => {
             let &(ref link_type, ref url, ref title) = allocs.index(link_ix);
             Tag::Link(*link_type, url.clone(), title.clone())
         }
@@ -2750,16 +4376,18 @@ fn item_to_event<'a>(item: Item, text: &'a str, allocs: &Allocations<'a>) -> Eve
             Tag::Image(*link_type, url.clone(), title.clone())
         }
         ItemBody::Heading(level) => Tag::Heading(level),
-        ItemBody::FencedCodeBlock(cow_ix) => {
-            Tag::CodeBlock(CodeBlockKind::Fenced(allocs[cow_ix].clone()))
+        ItemBody::FencedCodeBlock(info_ix) => {
+            let (language, attributes) = &allocs[info_ix];
+            Tag::CodeBlock(CodeBlockKind::Fenced(language.clone(), attributes.clone()))
         }
         ItemBody::IndentCodeBlock => Tag::CodeBlock(CodeBlockKind::Indented),
         ItemBody::BlockQuote => Tag::BlockQuote,
+        ItemBody::Admonition(kind) => Tag::Admonition(kind),
         ItemBody::List(_, c, listitem_start) => {
             if c == b'.' || c == b')' {
-                Tag::List(Some(listitem_start))
+                Tag::List(Some(listitem_start), char::from(c))
             } else {
-                Tag::List(None)
+                Tag::List(None, char::from(c))
             }
         }
         ItemBody::ListItem(_) => Tag::Item,
@@ -2774,6 +4402,30 @@ fn item_to_event<'a>(item: Item, text: &'a str, allocs: &Allocations<'a>) -> Eve
     Event::Start(tag)
 }
 
+/// Returns the start of the line of a top-level block starting at `ix`.
+fn block_line_start(bytes: &[u8], ix: usize) -> usize {
+    ix - scan_rev_while(&bytes[..ix], |c| c == b' ' || c == b'\t')
+}
+
+/// Returns the start of the line before the one starting at `ix`, if there is
+/// one and it is blank.
+fn blank_line_before(bytes: &[u8], ix: usize) -> Option<usize> {
+    let before = &bytes[..ix];
+    let line_end = if before.ends_with(b"\r\n") {
+        ix - 2
+    } else if before.ends_with(b"\n") || before.ends_with(b"\r") {
+        ix - 1
+    } else {
+        return None;
+    };
+    let line_start = line_end - scan_rev_while(&bytes[..line_end], |c| c != b'\n' && c != b'\r');
+    if scan_blank_line(&bytes[line_start..]) == Some(ix - line_start) {
+        Some(line_start)
+    } else {
+        None
+    }
+}
+
 // https://english.stackexchange.com/a/285573
 fn surgerize_tight_list(tree: &mut Tree<Item>, list_ix: TreeIndex) {
     let mut list_item = tree[list_ix].child;
@@ -2839,6 +4491,14 @@ impl<'a> Iterator for Parser<'a> {
                 let event = item_to_event(item, self.text, &self.allocs);
                 if let Event::Start(..) = event {
                     self.tree.push();
+                    // bare URLs can occur in text without any other inline markup,
+                    // so resolve the inline content of blocks up front
+                    if self.splits_text()
+                        && item.body.has_inline_content()
+                        && self.tree.cur() != TreePointer::Nil
+                    {
+                        self.handle_inline();
+                    }
                 } else {
                     self.tree.next_sibling(cur_ix);
                 }
@@ -2879,6 +4539,59 @@ mod test {
         assert_eq!(16, body_size);
     }
 
+    #[test]
+    fn event_count_matches_events() {
+        let docs = [
+            "",
+            "plain paragraph",
+            "# *emphasis* and **strong**\n\n> quote with [link](dest)\n",
+            "* a\n* b\n\n      code\n\n---\n",
+            "| a | b |\n|---|---|\n| `c` | <d> |\n",
+            "- [x] done[^1]\n\n[^1]: note\n",
+            "[unmatched *delims_ and ![image](src \"t\")\n",
+        ];
+        for doc in &docs {
+            let parser = parser_with_extensions(doc);
+            let count = parser.event_count();
+            let events = parser.collect::<Vec<_>>();
+            assert_eq!(count, events.len(), "for {:?}", doc);
+            assert_eq!(events, parser_with_extensions(doc).collect::<Vec<_>>());
+        }
+
+        let doc = "see www.example.com and *https://a.b*\n";
+        let parser = Parser::new_ext(doc, Options::ENABLE_GFM_AUTOLINKS);
+        let count = parser.event_count();
+        let events = parser.collect::<Vec<_>>();
+        assert_eq!(count, events.len());
+        assert_eq!(
+            events,
+            Parser::new_ext(doc, Options::ENABLE_GFM_AUTOLINKS).collect::<Vec<_>>()
+        );
+
+        let mut parser = parser_with_extensions("para *one*\n\npara two\n");
+        parser.nth(2);
+        assert_eq!(parser.event_count(), parser.count());
+    }
+
+    #[test]
+    fn event_count_reports_unresolved_references_once() {
+        let calls = std::cell::Cell::new(0);
+        let callback = |_: &str, _: Range<usize>| calls.set(calls.get() + 1);
+        let parser = Parser::new("[nope]").unresolved_reference_callback(&callback);
+        let count = parser.event_count();
+        assert_eq!(0, calls.get());
+        assert_eq!(count, parser.count());
+        assert_eq!(1, calls.get());
+    }
+
+    #[test]
+    fn empty_and_blank_documents() {
+        for &text in &["", "\n", "   ", "\n\n\n", "   \n\n", "\t\r\n \n"] {
+            assert_eq!(None, Parser::new(text).next(), "for {:?}", text);
+            assert_eq!(None, parser_with_extensions(text).next(), "for {:?}", text);
+        }
+    }
+
     #[test]
     fn single_open_fish_bracket() {
         // dont crash
@@ -2962,13 +4675,80 @@ mod test {
         parser_with_extensions("*__#_#__*").count();
     }
 
+    #[test]
+    fn emphasis_across_soft_break() {
+        let events: Vec<_> = Parser::new("*foo\nbar* **a\nb\nc**").collect();
+        let expected = vec![
+            Event::Start(Tag::Paragraph),
+            Event::Start(Tag::Emphasis),
+            Event::Text("foo".into()),
+            Event::SoftBreak,
+            Event::Text("bar".into()),
+            Event::End(Tag::Emphasis),
+            Event::Text(" ".into()),
+            Event::Start(Tag::Strong),
+            Event::Text("a".into()),
+            Event::SoftBreak,
+            Event::Text("b".into()),
+            Event::SoftBreak,
+            Event::Text("c".into()),
+            Event::End(Tag::Strong),
+            Event::End(Tag::Paragraph),
+        ];
+        assert_eq!(expected, events);
+    }
+
+    #[test]
+    fn emphasis_runs_without_content() {
+        for &c in &["*", "_"] {
+            for n in 2..=8 {
+                let run = c.repeat(n);
+                let text = format!("a {} b{}c", run, run);
+                let mut html = String::new();
+                crate::html::push_html(&mut html, Parser::new(&text));
+                assert_eq!(format!("<p>{}</p>\n", text), html);
+            }
+        }
+    }
+
+    #[test]
+    fn delimiter_run_between_spaces_stays_literal() {
+        // a run that can neither open nor close is merged back into the
+        // text around it
+        for &text in &["a ** b", "** a", "a **"] {
+            let events: Vec<_> = Parser::new(text).into_offset_iter().collect();
+            let expected = vec![
+                (Event::Start(Tag::Paragraph), 0..0),
+                (Event::Text(text.into()), 0..text.len()),
+                (Event::End(Tag::Paragraph), text.len()..text.len()),
+            ];
+            assert_eq!(expected, events);
+        }
+    }
+
+    #[test]
+    fn nested_containers() {
+        let text = "> 1. a[^n]\n>\n>    [^n]: > | x |\n>    > | - |\n>    > | y |\n>\n\
+                    >    ```\n>    code\n>    ```\n>    <div>\n>    lazy\n";
+        let expected = "<blockquote>\n<ol>\n<li>\n\
+                         <p>a<sup class=\"footnote-reference\"><a href=\"#n\">1</a></sup></p>\n\
+                         <div class=\"footnote-definition\" id=\"n\">\
+                         <sup class=\"footnote-definition-label\">1</sup>\n\
+                         <blockquote>\n<table><thead><tr><th>x</th></tr></thead><tbody>\n\
+                         <tr><td>y</td></tr>\n</tbody></table>\n</blockquote>\n</div>\n\
+                         <pre><code>code\n</code></pre>\n<div>\nlazy\n</li>\n</ol>\n</blockquote>\n";
+        let mut html = String::new();
+        crate::html::push_html(&mut html, parser_with_extensions(text));
+        assert_eq!(expected, html);
+    }
+
     #[test]
     fn offset_iter() {
         let event_offsets: Vec<_> = Parser::new("*hello* world")
             .into_offset_iter()
             .map(|(_ev, range)| range)
             .collect();
-        let expected_offsets = vec![(0..13), (0..7), (1..6), (0..7), (7..13), (0..13)];
+        let expected_offsets = vec![(0..0), (0..1), (1..6), (6..7), (7..13), (13..13)];
         assert_eq!(expected_offsets, event_offsets);
     }
 
@@ -2978,7 +4758,7 @@ mod test {
             .into_offset_iter()
             .map(|(_ev, range)| range)
             .collect();
-        let expected_offsets = vec![(0..10), (0..2), (2..8), (3..4), (2..8), (8..10), (0..10)];
+        let expected_offsets = vec![(0..0), (0..2), (2..3), (3..4), (4..8), (8..10), (10..10)];
         assert_eq!(expected_offsets, event_offsets);
     }
 
@@ -2992,6 +4772,1134 @@ mod test {
         assert_eq!(expected_offsets, event_offsets);
     }
 
+    #[test]
+    fn offset_iter_leading_escape() {
+        let events: Vec<_> = Parser::new_ext("\\$x$", Options::ENABLE_MATH)
+            .into_offset_iter()
+            .collect();
+        let expected = vec![
+            (Event::Start(Tag::Paragraph), 0..0),
+            (Event::Text("$x".into()), 1..3),
+            (Event::Text("$".into()), 3..4),
+            (Event::End(Tag::Paragraph), 4..4),
+        ];
+        assert_eq!(expected, events);
+
+        let (event, range) = Parser::new("*\\*x*").into_offset_iter().nth(1).unwrap();
+        assert_eq!(Event::Start(Tag::Emphasis), event);
+        assert_eq!(0..1, range);
+    }
+
+    #[test]
+    fn offset_iter_fenced_code_block() {
+        let text = "```rust\nfn main() {}\n```\n";
+        let event_offsets: Vec<_> = Parser::new(text)
+            .into_offset_iter()
+            .map(|(_ev, range)| &text[range])
+            .collect();
+        let expected_offsets = vec!["```rust\n", "fn main() {}\n", "```"];
+        assert_eq!(expected_offsets, event_offsets);
+    }
+
+    #[test]
+    fn offset_iter_table() {
+        let (event, range) = Parser::new_ext("x\n\na|b\n-|-\n", Options::ENABLE_TABLES)
+            .into_offset_iter()
+            .nth(3)
+            .unwrap();
+        assert_eq!(Event::Start(Tag::Table(vec![Alignment::None; 2])), event);
+        assert_eq!(3..3, range);
+    }
+
+    #[test]
+    fn offset_iter_gfm_autolinks() {
+        let text = "see http://example.com\n";
+        let events: Vec<_> = Parser::new_ext(text, Options::ENABLE_GFM_AUTOLINKS)
+            .into_offset_iter()
+            .map(|(event, _range)| event)
+            .collect();
+        let expected: Vec<_> = Parser::new_ext(text, Options::ENABLE_GFM_AUTOLINKS).collect();
+        assert_eq!(expected, events);
+    }
+
+    #[test]
+    fn source_slices_offsets() {
+        let parser = Parser::new("a *b* c");
+        let source = parser.source();
+        let ranges: Vec<_> = parser
+            .into_offset_iter()
+            .filter(|(event, _range)| *event == Event::Start(Tag::Emphasis))
+            .map(|(_event, range)| range)
+            .collect();
+        assert_eq!(vec![2..3], ranges);
+        assert_eq!("*", &source[ranges[0].clone()]);
+    }
+
+    #[test]
+    fn emphasis_delimiter_from_offsets() {
+        let parser = Parser::new("*x* _x_ **x** __x__ *_x_*");
+        let source = parser.source();
+        let delims: Vec<_> = parser
+            .into_offset_iter()
+            .filter_map(|(event, range)| match event {
+                Event::Start(tag @ Tag::Emphasis) | Event::Start(tag @ Tag::Strong) => {
+                    Some((tag, &source[range.start..range.start + 1]))
+                }
+                _ => None,
+            })
+            .collect();
+        let expected = vec![
+            (Tag::Emphasis, "*"),
+            (Tag::Emphasis, "_"),
+            (Tag::Strong, "*"),
+            (Tag::Strong, "_"),
+            (Tag::Emphasis, "*"),
+            (Tag::Emphasis, "_"),
+        ];
+        assert_eq!(expected, delims);
+    }
+
+    #[test]
+    fn offset_iter_fence_after_paragraph() {
+        let events: Vec<_> = Parser::new("text\n   ```\ncode\n```\n")
+            .into_offset_iter()
+            .collect();
+        let expected = vec![
+            (Event::Start(Tag::Paragraph), 0..0),
+            (Event::Text("text".into()), 0..4),
+            (Event::End(Tag::Paragraph), 4..5),
+            (
+                Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced("".into(), "".into()))),
+                8..12,
+            ),
+            (Event::Text("code\n".into()), 12..17),
+            (
+                Event::End(Tag::CodeBlock(CodeBlockKind::Fenced("".into(), "".into()))),
+                17..20,
+            ),
+        ];
+        assert_eq!(expected, events);
+    }
+
+    #[test]
+    fn offset_iter_unclosed_containers_at_eof() {
+        fn ends(text: &str) -> Vec<(Event<'_>, Range<usize>)> {
+            Parser::new(text)
+                .into_offset_iter()
+                .filter_map(|(event, range)| match event {
+                    Event::End(_) => Some((event, range)),
+                    _ => None,
+                })
+                .collect()
+        }
+
+        let expected = vec![
+            (Event::End(Tag::Paragraph), 3..3),
+            (Event::End(Tag::BlockQuote), 3..3),
+        ];
+        assert_eq!(expected, ends("> a"));
+
+        let expected = vec![
+            (Event::End(Tag::Item), 3..3),
+            (Event::End(Tag::List(None, '-')), 3..3),
+        ];
+        assert_eq!(expected, ends("- a"));
+
+        let expected = vec![(
+            Event::End(Tag::CodeBlock(CodeBlockKind::Fenced("".into(), "".into()))),
+            8..8,
+        )];
+        assert_eq!(expected, ends("```\ncode"));
+    }
+
+    #[test]
+    fn reparse_matches_full_parse() {
+        fn check(old: &str, start: usize, end: usize, replacement: &str) {
+            let new = format!("{}{}{}", &old[..start], replacement, &old[end..]);
+            let options = Options::all();
+            let mut parser = Parser::new_ext(old, options);
+            // part of the old source may have been consumed already
+            parser.next();
+            parser.reparse(start..end, &new);
+            let events: Vec<_> = parser.into_offset_iter().collect();
+            let expected: Vec<_> = Parser::new_ext(&new, options).into_offset_iter().collect();
+            assert_eq!(expected, events, "{:?}", new);
+        }
+
+        let doc = "# title\n\nfirst *para*\n\n- a\n- b\n\n> quote\n\nlast\n";
+        // inside a paragraph in the middle
+        check(doc, 15, 21, "**bold**");
+        // turning a paragraph into a code block that swallows the rest
+        check(doc, 9, 9, "```\n");
+        // joining two blocks by removing the blank line between them
+        check(doc, 31, 32, "");
+        // at the end of the source
+        check(doc, doc.len(), doc.len(), "more\n");
+        // inside the first block
+        check(doc, 2, 7, "heading");
+        // a reference definition defined after its use
+        check(doc, doc.len(), doc.len(), "\n[para]: /url\n");
+        check("[a]\n\nb\n\n[a]: /url\n", 5, 6, "c");
+    }
+
+    #[test]
+    fn offset_iter_multibyte_chars_at_block_boundaries() {
+        let text = "# é\u{a0}\n\né para ü\n\n- é\n- ü\u{3000}\n\n1. 日本\n\n```é\né\n```\n\n\
+                    Setext ü\n===\n\n> é\n\n    é\n\n|é|\n|-|\n|ü|\n";
+        let mut texts = Vec::new();
+        for (event, range) in parser_with_extensions(text).into_offset_iter() {
+            assert!(text.is_char_boundary(range.start), "{:?}", event);
+            assert!(text.is_char_boundary(range.end), "{:?}", event);
+            if let Event::Text(s) = event {
+                assert_eq!(&text[range], &*s);
+                texts.push(s.into_string());
+            }
+        }
+        let expected = vec![
+            "é\u{a0}",
+            "é para ü",
+            "é",
+            "ü\u{3000}",
+            "日本",
+            "é\n",
+            "Setext ü",
+            "é",
+            "é\n",
+            "é",
+            "ü",
+        ];
+        assert_eq!(expected, texts);
+    }
+
+    #[test]
+    fn offset_iter_closing_fence() {
+        let text = "```\ncode\n  `````  \nafter\n";
+        let closing: Vec<_> = Parser::new(text)
+            .into_offset_iter()
+            .filter_map(|(event, range)| match event {
+                Event::End(Tag::CodeBlock(_)) => Some(&text[range]),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(vec!["  `````  "], closing);
+    }
+
+    #[test]
+    fn offset_iter_rule_between_paragraphs() {
+        let events: Vec<_> = Parser::new("a\n\n***\n\nb\n").into_offset_iter().collect();
+        let expected = vec![
+            (Event::Start(Tag::Paragraph), 0..0),
+            (Event::Text("a".into()), 0..1),
+            (Event::End(Tag::Paragraph), 1..2),
+            (Event::Rule, 3..7),
+            (Event::Start(Tag::Paragraph), 8..8),
+            (Event::Text("b".into()), 8..9),
+            (Event::End(Tag::Paragraph), 9..10),
+        ];
+        assert_eq!(expected, events);
+    }
+
+    fn break_offsets(text: &str) -> Vec<(Event<'_>, Range<usize>)> {
+        Parser::new(text)
+            .into_offset_iter()
+            .filter_map(|(event, range)| match event {
+                Event::SoftBreak | Event::HardBreak(_) => Some((event, range)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn lists_are_never_empty() {
+        for &text in &[
+            "- - -",
+            "* * *",
+            "- - - a",
+            "- -",
+            "-\n-\n-",
+            "1. - - -",
+            "- \n\n- -\n",
+        ] {
+            let events: Vec<_> = Parser::new(text).collect();
+            for (i, event) in events.iter().enumerate() {
+                if let Event::Start(Tag::List(..)) = event {
+                    assert_eq!(Event::Start(Tag::Item), events[i + 1], "{:?}", text);
+                }
+            }
+        }
+        assert_eq!(vec![Event::Rule], Parser::new("- - -").collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn offset_iter_soft_break() {
+        assert_eq!(vec![(Event::SoftBreak, 1..2)], break_offsets("a\nb"));
+        assert_eq!(vec![(Event::SoftBreak, 1..3)], break_offsets("a\r\nb"));
+        assert_eq!(vec![(Event::SoftBreak, 1..3)], break_offsets("a \nb"));
+        assert_eq!(vec![(Event::SoftBreak, 3..4)], break_offsets("> a\n> b"));
+    }
+
+    #[test]
+    fn soft_break_before_lazy_list_item_line() {
+        let events: Vec<_> = Parser::new("- foo\nbar\n").collect();
+        let expected = vec![
+            Event::Start(Tag::List(None, '-')),
+            Event::Start(Tag::Item),
+            Event::Text("foo".into()),
+            Event::SoftBreak,
+            Event::Text("bar".into()),
+            Event::End(Tag::Item),
+            Event::End(Tag::List(None, '-')),
+        ];
+        assert_eq!(expected, events);
+        assert_eq!(vec![(Event::SoftBreak, 5..6)], break_offsets("- foo\nbar"));
+        assert_eq!(
+            vec![(Event::SoftBreak, 11..12)],
+            break_offsets("- a\n  - foo\nbar")
+        );
+    }
+
+    #[test]
+    fn offset_iter_hard_break() {
+        let spaces = Event::HardBreak(HardBreakStyle::Spaces);
+        let backslash = Event::HardBreak(HardBreakStyle::Backslash);
+        assert_eq!(vec![(spaces.clone(), 1..4)], break_offsets("a  \nb"));
+        assert_eq!(vec![(spaces.clone(), 1..4)], break_offsets("a\t\t\nb"));
+        assert_eq!(vec![(backslash, 1..3)], break_offsets("a\\\nb"));
+        assert_eq!(vec![(spaces, 1..5)], break_offsets("a  \r\nb"));
+    }
+
+    #[test]
+    fn hard_break_round_trip() {
+        let text = "a  \nb\\\nc";
+        let markdown: String = Parser::new(text)
+            .filter_map(|event| match event {
+                Event::Text(text) => Some(text.into_string()),
+                Event::HardBreak(HardBreakStyle::Spaces) => Some("  \n".to_owned()),
+                Event::HardBreak(HardBreakStyle::Backslash) => Some("\\\n".to_owned()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(text, markdown);
+    }
+
+    #[test]
+    fn no_hard_break_at_end_of_paragraph() {
+        for &(text, expected) in &[
+            ("foo\\", "foo\\"),
+            ("foo  ", "foo"),
+            ("foo\\\n\nbar", "foo\\"),
+        ] {
+            let events: Vec<_> = Parser::new(text).take(4).collect();
+            let expected_events = [
+                Event::Start(Tag::Paragraph),
+                Event::Text(expected.into()),
+                Event::End(Tag::Paragraph),
+            ];
+            assert_eq!(&expected_events[..], &events[..3], "for {:?}", text);
+        }
+    }
+
+    #[test]
+    fn front_matter() {
+        let yaml = "---\ntitle: a\ntags: [b]\n---\ntext\n\n---\n";
+        let events: Vec<_> = Parser::new_ext(yaml, Options::ENABLE_FRONT_MATTER).collect();
+        let expected = vec![
+            Event::FrontMatter("title: a\ntags: [b]\n".into()),
+            Event::Start(Tag::Paragraph),
+            Event::Text("text".into()),
+            Event::End(Tag::Paragraph),
+            Event::Rule,
+        ];
+        assert_eq!(expected, events);
+
+        let toml = "+++\ntitle = \"a\"\n+++\n";
+        let events: Vec<_> = Parser::new_ext(toml, Options::ENABLE_FRONT_MATTER).collect();
+        assert_eq!(vec![Event::FrontMatter("title = \"a\"\n".into())], events);
+
+        // only at the very start, and only when closed
+        for &text in &["\n---\na\n---\n", "---\na\n", "--- a\nb\n---\n"] {
+            let events: Vec<_> = Parser::new_ext(text, Options::ENABLE_FRONT_MATTER).collect();
+            assert_eq!(
+                events,
+                Parser::new(text).collect::<Vec<_>>(),
+                "for {:?}",
+                text
+            );
+        }
+    }
+
+    #[test]
+    fn line_column_with_tabs() {
+        let text = "a\n\tb\r\n ab\tc\r\r\n\u{e9}\t\td";
+        assert_eq!((1, 1), line_column(text, 0));
+        assert_eq!((1, 2), line_column(text, 1));
+        assert_eq!((2, 5), line_column(text, 3));
+        assert_eq!((2, 6), line_column(text, 4));
+        assert_eq!((3, 4), line_column(text, 9));
+        assert_eq!((3, 5), line_column(text, 10));
+        assert_eq!((4, 1), line_column(text, 12));
+        assert_eq!((5, 9), line_column(text, 18));
+        assert_eq!((5, 10), line_column(text, text.len()));
+    }
+
+    #[test]
+    fn depth_iter() {
+        let depths: Vec<_> = Parser::new("> - a\n>   - b\n")
+            .with_depth()
+            .map(|(depth, _ev)| depth)
+            .collect();
+        // blockquote, list, item, text, list, item, text, /item, /list, /item, /list, /blockquote
+        let expected_depths = vec![0, 1, 2, 3, 3, 4, 5, 4, 3, 2, 1, 0];
+        assert_eq!(expected_depths, depths);
+    }
+
+    #[test]
+    fn list_marker_characters() {
+        let lists: Vec<_> = Parser::new("- a\n+ b\n* c\n\n3. d\n4) e\n")
+            .filter_map(|event| match event {
+                Event::Start(Tag::List(start, c)) => Some((start, c)),
+                _ => None,
+            })
+            .collect();
+        let expected = vec![
+            (None, '-'),
+            (None, '+'),
+            (None, '*'),
+            (Some(3), '.'),
+            (Some(4), ')'),
+        ];
+        assert_eq!(expected, lists);
+    }
+
+    #[test]
+    fn list_item_marker_offsets() {
+        let text = "- a\n* b\n\n10. c\n  2)  d\n";
+        let markers: Vec<_> = Parser::new(text)
+            .into_offset_iter()
+            .filter_map(|(event, range)| match event {
+                Event::Start(Tag::Item) => Some(&text[range]),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(vec!["- ", "* ", "10. ", "  2)  "], markers);
+    }
+
+    #[test]
+    fn code_block_single_text() {
+        fn code_text(text: &str) -> Vec<CowStr<'_>> {
+            Parser::new_ext(text, Options::ENABLE_CODE_BLOCK_SINGLE_TEXT)
+                .filter_map(|event| match event {
+                    Event::Text(text) => Some(text),
+                    _ => None,
+                })
+                .collect()
+        }
+
+        // contiguous in the source
+        let texts = code_text("```\na\n\tb\n```\n\n    c\n");
+        assert_eq!(vec![CowStr::from("a\n\tb\n"), "c\n".into()], texts);
+        let borrowed = texts.iter().filter_map(|text| match text {
+            CowStr::Borrowed(text) => Some(*text),
+            _ => None,
+        });
+        assert_eq!(vec!["a\n\tb\n", "c\n"], borrowed.collect::<Vec<_>>());
+
+        // assembled from normalized lines
+        let cases = [
+            ("> ```\n> a\n> b\n> ```\n", "a\nb\n"),
+            ("```\r\na\r\nb\r\n```\r\n", "a\nb\n"),
+            ("    c\n     d\n", "c\n d\n"),
+            ("-     a\n\n       b\n", "a\n\n b\n"),
+        ];
+        for &(text, expected) in &cases {
+            assert_eq!(
+                vec![CowStr::from(expected)],
+                code_text(text),
+                "for {:?}",
+                text
+            );
+        }
+    }
+
+    #[test]
+    fn filter_html() {
+        let events: Vec<_> = Parser::new("a\n\n<div>\n*x*\n</div>\n\nb <br> c\n")
+            .filter_html()
+            .collect();
+        let expected = vec![
+            Event::Start(Tag::Paragraph),
+            Event::Text("a".into()),
+            Event::End(Tag::Paragraph),
+            Event::Start(Tag::Paragraph),
+            Event::Text("b ".into()),
+            Event::Text(" c".into()),
+            Event::End(Tag::Paragraph),
+        ];
+        assert_eq!(expected, events);
+    }
+
+    #[test]
+    fn visitor_rewrites_link_destinations() {
+        struct Rebase;
+
+        impl<'a> Visitor<'a> for Rebase {
+            fn link(
+                &mut self,
+                _link_type: LinkType,
+                dest: CowStr<'a>,
+                title: CowStr<'a>,
+            ) -> (CowStr<'a>, CowStr<'a>) {
+                if dest.contains(':') {
+                    (dest, title)
+                } else {
+                    (format!("https://example.com/{}", dest).into(), title)
+                }
+            }
+        }
+
+        let text = "[a](x) <http://y> [b][r] ![c](x)\n\n[r]: z \"t\"\n";
+        let links: Vec<_> = Parser::new(text)
+            .visit(Rebase)
+            .filter_map(|event| match event {
+                Event::Start(Tag::Link(_, dest, title)) => Some(("start", dest, title)),
+                Event::End(Tag::Link(_, dest, title)) => Some(("end", dest, title)),
+                Event::Start(Tag::Image(_, dest, title)) => Some(("image", dest, title)),
+                _ => None,
+            })
+            .collect();
+        let expected = vec![
+            ("start", "https://example.com/x".into(), "".into()),
+            ("end", "https://example.com/x".into(), "".into()),
+            ("start", "http://y".into(), "".into()),
+            ("end", "http://y".into(), "".into()),
+            ("start", "https://example.com/z".into(), "t".into()),
+            ("end", "https://example.com/z".into(), "t".into()),
+            ("image", "x".into(), "".into()),
+        ];
+        assert_eq!(expected, links);
+    }
+
+    #[test]
+    fn spoiler() {
+        let events: Vec<_> = Parser::new_ext("a ||b|| |c| |", Options::ENABLE_SPOILER).collect();
+        let expected = vec![
+            Event::Start(Tag::Paragraph),
+            Event::Text("a ".into()),
+            Event::Start(Tag::Spoiler),
+            Event::Text("b".into()),
+            Event::End(Tag::Spoiler),
+            Event::Text(" |c| |".into()),
+            Event::End(Tag::Paragraph),
+        ];
+        assert_eq!(expected, events);
+    }
+
+    #[test]
+    fn spoiler_pipes_in_tables() {
+        // pipes separate cells, so a spoiler can't span them
+        let text = "a || b\n-|-|-\n||c||\n";
+        let options = Options::ENABLE_SPOILER | Options::ENABLE_TABLES;
+        let cells = Parser::new_ext(text, options)
+            .filter(|event| *event == Event::Start(Tag::TableCell))
+            .count();
+        assert_eq!(6, cells);
+        assert!(Parser::new_ext(text, options).all(|event| event != Event::Start(Tag::Spoiler)));
+    }
+
+    #[test]
+    fn highlight() {
+        let events: Vec<_> =
+            Parser::new_ext("a ==b== =c= = ===d===", Options::ENABLE_HIGHLIGHT).collect();
+        let expected = vec![
+            Event::Start(Tag::Paragraph),
+            Event::Text("a ".into()),
+            Event::Start(Tag::Highlight),
+            Event::Text("b".into()),
+            Event::End(Tag::Highlight),
+            Event::Text(" =c= = ===d===".into()),
+            Event::End(Tag::Paragraph),
+        ];
+        assert_eq!(expected, events);
+    }
+
+    #[test]
+    fn highlight_setext_underline() {
+        let text = "==a==\n==\n\nb\n==c==\n";
+        let events: Vec<_> = Parser::new_ext(text, Options::ENABLE_HIGHLIGHT).collect();
+        let expected = vec![
+            Event::Start(Tag::Heading(1)),
+            Event::Start(Tag::Highlight),
+            Event::Text("a".into()),
+            Event::End(Tag::Highlight),
+            Event::End(Tag::Heading(1)),
+            Event::Start(Tag::Paragraph),
+            Event::Text("b".into()),
+            Event::SoftBreak,
+            Event::Start(Tag::Highlight),
+            Event::Text("c".into()),
+            Event::End(Tag::Highlight),
+            Event::End(Tag::Paragraph),
+        ];
+        assert_eq!(expected, events);
+    }
+
+    #[test]
+    fn registered_delimiter() {
+        let render = |text| {
+            let mut html = String::new();
+            let parser = Parser::new(text).register_delimiter('=', 2, Tag::Highlight);
+            crate::html::push_html(&mut html, parser);
+            html
+        };
+        assert_eq!("<p>a <mark>b</mark> c</p>\n", render("a ==b== c"));
+        assert_eq!(
+            "<p><mark>a <em>b</em> <mark>c</mark>&amp;</mark></p>\n",
+            render("==a *b* ===c===&==")
+        );
+        // runs must be long enough and of equal length, and can be escaped
+        assert_eq!("<p>=a= ==b===</p>\n", render("=a= ==b==="));
+        assert_eq!("<p>==c==</p>\n", render("\\==c=="));
+        // flanking rules apply, and code spans take precedence
+        assert_eq!(
+            "<p>a == b == c<code>==</code>d==</p>\n",
+            render("a == b == c`==`d==")
+        );
+
+        let events: Vec<_> = Parser::new("x ==y==")
+            .register_delimiter('=', 2, Tag::Highlight)
+            .into_offset_iter()
+            .collect();
+        let expected = vec![
+            (Event::Start(Tag::Paragraph), 0..0),
+            (Event::Text("x ".into()), 0..2),
+            (Event::Start(Tag::Highlight), 2..4),
+            (Event::Text("y".into()), 4..5),
+            (Event::End(Tag::Highlight), 5..7),
+            (Event::End(Tag::Paragraph), 7..7),
+        ];
+        assert_eq!(expected, events);
+    }
+
+    #[test]
+    #[should_panic]
+    fn register_emphasis_delimiter() {
+        let _ = Parser::new("").register_delimiter('*', 2, Tag::Highlight);
+    }
+
+    #[test]
+    fn inline_math() {
+        let events: Vec<_> = Parser::new_ext("a $x^2$ b $ c$", Options::ENABLE_MATH).collect();
+        let expected = vec![
+            Event::Start(Tag::Paragraph),
+            Event::Text("a ".into()),
+            Event::InlineMath("x^2".into()),
+            Event::Text(" b $ c".into()),
+            Event::Text("$".into()),
+            Event::End(Tag::Paragraph),
+        ];
+        assert_eq!(expected, events);
+    }
+
+    #[test]
+    fn inline_math_after_links_and_code_spans() {
+        // dollar signs in link destinations and code spans can't delimit math
+        let events: Vec<_> = Parser::new_ext("[a](u$)$ `x$`$ $y$", Options::ENABLE_MATH).collect();
+        let expected = vec![
+            Event::Start(Tag::Paragraph),
+            Event::Start(Tag::Link(LinkType::Inline, "u$".into(), "".into())),
+            Event::Text("a".into()),
+            Event::End(Tag::Link(LinkType::Inline, "u$".into(), "".into())),
+            Event::Text("$".into()),
+            Event::Text(" ".into()),
+            Event::Code("x$".into()),
+            Event::Text("$".into()),
+            Event::Text(" ".into()),
+            Event::InlineMath("y".into()),
+            Event::End(Tag::Paragraph),
+        ];
+        assert_eq!(expected, events);
+
+        // math can still contain a code span
+        let events: Vec<_> = Parser::new_ext("$a `b` c$", Options::ENABLE_MATH).collect();
+        assert_eq!(Event::InlineMath("a `b` c".into()), events[1]);
+    }
+
+    #[test]
+    fn inline_math_disabled() {
+        let events: Vec<_> = Parser::new("$x^2$").collect();
+        let expected = vec![
+            Event::Start(Tag::Paragraph),
+            Event::Text("$x^2$".into()),
+            Event::End(Tag::Paragraph),
+        ];
+        assert_eq!(expected, events);
+    }
+
+    #[test]
+    fn display_math_in_container() {
+        let events: Vec<_> =
+            Parser::new_ext("> $$\n> \\sum *x*\n> $$\n", Options::ENABLE_MATH).collect();
+        let expected = vec![
+            Event::Start(Tag::BlockQuote),
+            Event::DisplayMath("\n\\sum *x*\n".into()),
+            Event::End(Tag::BlockQuote),
+        ];
+        assert_eq!(expected, events);
+    }
+
+    #[test]
+    fn unclosed_display_math() {
+        let events: Vec<_> = Parser::new_ext("$$\nx\n\n$$", Options::ENABLE_MATH).collect();
+        let expected = vec![
+            Event::Start(Tag::Paragraph),
+            Event::Text("$$".into()),
+            Event::SoftBreak,
+            Event::Text("x".into()),
+            Event::End(Tag::Paragraph),
+            Event::Start(Tag::Paragraph),
+            Event::Text("$$".into()),
+            Event::End(Tag::Paragraph),
+        ];
+        assert_eq!(expected, events);
+    }
+
+    #[test]
+    fn admonition_kinds() {
+        fn kind(text: &str) -> Option<AdmonitionKind> {
+            match Parser::new_ext(text, Options::ENABLE_ADMONITIONS).next() {
+                Some(Event::Start(Tag::Admonition(kind))) => Some(kind),
+                _ => None,
+            }
+        }
+
+        assert_eq!(Some(AdmonitionKind::Note), kind("> [!NOTE]\n> a\n"));
+        assert_eq!(Some(AdmonitionKind::Tip), kind("> [!TIP]\n> a\n"));
+        assert_eq!(
+            Some(AdmonitionKind::Important),
+            kind("> [!IMPORTANT]\n> a\n")
+        );
+        assert_eq!(Some(AdmonitionKind::Warning), kind("> [!WARNING]\n> a\n"));
+        assert_eq!(Some(AdmonitionKind::Caution), kind("> [!CAUTION]\n> a\n"));
+        assert_eq!(Some(AdmonitionKind::Note), kind(">[!note]  \r\n> a\n"));
+        assert_eq!(Some(AdmonitionKind::Warning), kind("> [!Warning]"));
+        assert_eq!(None, kind("> [!NOTE] a\n"));
+        assert_eq!(None, kind("> [!NOTICE]\n> a\n"));
+        assert_eq!(None, kind("> [NOTE]\n> a\n"));
+    }
+
+    #[test]
+    fn admonition_marker_line_is_consumed() {
+        let events: Vec<_> =
+            Parser::new_ext("> [!TIP]\n> *a*\n", Options::ENABLE_ADMONITIONS).collect();
+        let expected = vec![
+            Event::Start(Tag::Admonition(AdmonitionKind::Tip)),
+            Event::Start(Tag::Paragraph),
+            Event::Start(Tag::Emphasis),
+            Event::Text("a".into()),
+            Event::End(Tag::Emphasis),
+            Event::End(Tag::Paragraph),
+            Event::End(Tag::Admonition(AdmonitionKind::Tip)),
+        ];
+        assert_eq!(expected, events);
+    }
+
+    #[test]
+    fn blockquote_without_admonition_marker() {
+        let expected = vec![
+            Event::Start(Tag::BlockQuote),
+            Event::Start(Tag::Paragraph),
+            Event::Text("a".into()),
+            Event::SoftBreak,
+            Event::Text("[".into()),
+            Event::Text("!NOTE".into()),
+            Event::Text("]".into()),
+            Event::End(Tag::Paragraph),
+            Event::End(Tag::BlockQuote),
+        ];
+        let events: Vec<_> =
+            Parser::new_ext("> a\n> [!NOTE]\n", Options::ENABLE_ADMONITIONS).collect();
+        assert_eq!(expected, events);
+
+        // without the option, the marker is plain text
+        let first = Parser::new("> [!NOTE]\n> a\n").next();
+        assert_eq!(Some(Event::Start(Tag::BlockQuote)), first);
+    }
+
+    #[test]
+    fn code_block_info_string_unescaped() {
+        fn info_string(text: &str) -> (CowStr<'_>, CowStr<'_>) {
+            match Parser::new(text).next() {
+                Some(Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(language, attributes)))) => {
+                    (language, attributes)
+                }
+                other => panic!("expected fenced code block, got {:?}", other),
+            }
+        }
+        assert_eq!(
+            info_string("```c&#43;&#43;\n```\n"),
+            ("c++".into(), "".into())
+        );
+        assert_eq!(
+            info_string("```  c\\+\\+  \n```\n"),
+            ("c++".into(), "".into())
+        );
+        assert_eq!(
+            info_string("~~~ a\\&amp;b &quot;c\n~~~\n"),
+            ("a&amp;b".into(), "&quot;c".into())
+        );
+        assert_eq!(
+            info_string("```rust,no_run\n```\n"),
+            ("rust,no_run".into(), "".into())
+        );
+        assert_eq!(
+            info_string("```mermaid  {theme=dark}\\ \n```\n"),
+            ("mermaid".into(), "{theme=dark}\\".into())
+        );
+    }
+
+    #[test]
+    fn tight_item_keeps_non_paragraph_blocks() {
+        // Only paragraphs that are direct children of the item lose their tags.
+        let events: Vec<_> = Parser::new("- text\n  ```\n  code\n  ```\n  > quote\n").collect();
+        let expected = vec![
+            Event::Start(Tag::List(None, '-')),
+            Event::Start(Tag::Item),
+            Event::Text("text".into()),
+            Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced("".into(), "".into()))),
+            Event::Text("code\n".into()),
+            Event::End(Tag::CodeBlock(CodeBlockKind::Fenced("".into(), "".into()))),
+            Event::Start(Tag::BlockQuote),
+            Event::Start(Tag::Paragraph),
+            Event::Text("quote".into()),
+            Event::End(Tag::Paragraph),
+            Event::End(Tag::BlockQuote),
+            Event::End(Tag::Item),
+            Event::End(Tag::List(None, '-')),
+        ];
+        assert_eq!(expected, events);
+    }
+
+    #[test]
+    fn blank_line_before_indented_code_loosens_item() {
+        // The blank line needed to start an indented code block after a
+        // paragraph makes the item loose, so its paragraph is kept.
+        let events: Vec<_> = Parser::new("- text\n\n      code\n").collect();
+        let expected = vec![
+            Event::Start(Tag::List(None, '-')),
+            Event::Start(Tag::Item),
+            Event::Start(Tag::Paragraph),
+            Event::Text("text".into()),
+            Event::End(Tag::Paragraph),
+            Event::Start(Tag::CodeBlock(CodeBlockKind::Indented)),
+            Event::Text("code\n".into()),
+            Event::End(Tag::CodeBlock(CodeBlockKind::Indented)),
+            Event::End(Tag::Item),
+            Event::End(Tag::List(None, '-')),
+        ];
+        assert_eq!(expected, events);
+    }
+
+    #[test]
+    fn escaped_pipe_outside_table() {
+        fn check(parser: Parser) {
+            let events: Vec<_> = parser.collect();
+            let text: String = events
+                .iter()
+                .filter_map(|event| match event {
+                    Event::Text(text) => Some(&**text),
+                    _ => None,
+                })
+                .collect();
+            assert_eq!("a | b", text);
+            assert_eq!(Event::Start(Tag::Paragraph), events[0]);
+        }
+
+        check(Parser::new("a \\| b\n"));
+        check(parser_with_extensions("a \\| b\n"));
+    }
+
+    #[test]
+    fn code_span_content_is_literal() {
+        let code: Vec<_> = Parser::new("`a\\b` `<tag>` `a\\`\n")
+            .filter_map(|event| match event {
+                Event::Code(code) => Some(code),
+                _ => None,
+            })
+            .collect();
+        let expected: Vec<CowStr> = vec!["a\\b".into(), "<tag>".into(), "a\\".into()];
+        assert_eq!(expected, code);
+    }
+
+    #[test]
+    fn code_spans_and_code_blocks_are_distinct() {
+        let text = "`a`\n\n```\n`b`\n```\n\n    `c`\n\n> `d`\n";
+        let mut code_blocks = Vec::new();
+        let mut code_spans = Vec::new();
+        let mut in_code_block = false;
+        for (event, range) in Parser::new(text).into_offset_iter() {
+            match event {
+                Event::Start(Tag::CodeBlock(_)) => in_code_block = true,
+                Event::End(Tag::CodeBlock(_)) => in_code_block = false,
+                Event::Text(ref text) if in_code_block => code_blocks.push(text.clone()),
+                Event::Code(code) => {
+                    assert!(!in_code_block);
+                    code_spans.push((code, range));
+                }
+                _ => (),
+            }
+        }
+        let expected_blocks: Vec<CowStr> = vec!["`b`\n".into(), "`c`\n".into()];
+        assert_eq!(expected_blocks, code_blocks);
+        let expected_spans = vec![(CowStr::from("a"), 0..3), ("d".into(), 29..32)];
+        assert_eq!(expected_spans, code_spans);
+    }
+
+    #[test]
+    fn try_new_max_len() {
+        let err = Parser::try_new("# Title\n", Options::empty(), 7).err();
+        let expected = TooLarge { len: 8, max_len: 7 };
+        assert_eq!(Some(expected), err);
+
+        let parser = Parser::try_new("# Title\n", Options::empty(), 8).unwrap();
+        assert_eq!(3, parser.count());
+    }
+
+    #[test]
+    fn loose_list_keeps_paragraphs() {
+        let events: Vec<_> = Parser::new("1. a\n\n2. b\n").collect();
+        let expected = vec![
+            Event::Start(Tag::List(Some(1), '.')),
+            Event::Start(Tag::Item),
+            Event::Start(Tag::Paragraph),
+            Event::Text("a".into()),
+            Event::End(Tag::Paragraph),
+            Event::End(Tag::Item),
+            Event::Start(Tag::Item),
+            Event::Start(Tag::Paragraph),
+            Event::Text("b".into()),
+            Event::End(Tag::Paragraph),
+            Event::End(Tag::Item),
+            Event::End(Tag::List(Some(1), '.')),
+        ];
+        assert_eq!(expected, events);
+    }
+
+    #[test]
+    fn duplicate_reference_definitions_first_wins() {
+        let text = "[a]: /first\n[A]: /second 't'\n\n[a] [A][]\n\n[ a ]: /third\n";
+        let urls: Vec<_> = Parser::new(text)
+            .filter_map(|event| match event {
+                Event::Start(Tag::Link(_, url, title)) => Some((url, title)),
+                _ => None,
+            })
+            .collect();
+        let first = (CowStr::from("/first"), CowStr::from(""));
+        assert_eq!(vec![first.clone(), first], urls);
+    }
+
+    #[test]
+    fn reference_definitions_stripped() {
+        let events: Vec<_> = Parser::new("[a]: /u 'title'\n\n[a]").collect();
+        let expected = vec![
+            Event::Start(Tag::Paragraph),
+            Event::Start(Tag::Link(LinkType::Shortcut, "/u".into(), "title".into())),
+            Event::Text("a".into()),
+            Event::End(Tag::Link(LinkType::Shortcut, "/u".into(), "title".into())),
+            Event::End(Tag::Paragraph),
+        ];
+        assert_eq!(expected, events);
+    }
+
+    #[test]
+    fn only_reference_definitions() {
+        let text = "[a]: /x\n[b]: /y\n\n[c]:\n/z 'title'\n";
+        assert_eq!(0, Parser::new(text).count());
+
+        let events: Vec<_> = Parser::new_ext(text, Options::ENABLE_REFERENCE_DEFINITIONS).collect();
+        let expected = vec![
+            Event::ReferenceDefinition("[a]: /x".into()),
+            Event::ReferenceDefinition("[b]: /y".into()),
+            Event::ReferenceDefinition("[c]:\n/z 'title'".into()),
+        ];
+        assert_eq!(expected, events);
+    }
+
+    #[test]
+    fn reference_definition_ranges() {
+        let text = "[A]: /x\n> [b]:\n> </y> \"t\"  \n\n[a]: /dup\n[c]: /z\n";
+        let parser = Parser::new(text);
+        let defs: Vec<_> = parser.reference_definitions().collect();
+        let expected = vec![
+            ("A", "/x", "", 0..7),
+            ("b", "/y", "t", 10..25),
+            ("a", "/dup", "", 29..38),
+            ("c", "/z", "", 39..46),
+        ];
+        assert_eq!(expected, defs);
+        assert_eq!("[b]:\n> </y> \"t\"", &text[defs[1].3.clone()]);
+
+        // links use the first definition of a label
+        let links: Vec<_> = Parser::new("[a]\n\n[A]: /x\n[a]: /dup\n")
+            .filter_map(|event| match event {
+                Event::Start(Tag::Link(_, dest, _)) => Some(dest),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(vec![CowStr::from("/x")], links);
+    }
+
+    #[test]
+    fn reference_definitions_preserved() {
+        let events: Vec<_> = Parser::new_ext(
+            "[a]: /u 'title'\n\n[a]\n\n> [b]:\n> /v  \n",
+            Options::ENABLE_REFERENCE_DEFINITIONS,
+        )
+        .collect();
+        let expected = vec![
+            Event::ReferenceDefinition("[a]: /u 'title'".into()),
+            Event::Start(Tag::Paragraph),
+            Event::Start(Tag::Link(LinkType::Shortcut, "/u".into(), "title".into())),
+            Event::Text("a".into()),
+            Event::End(Tag::Link(LinkType::Shortcut, "/u".into(), "title".into())),
+            Event::End(Tag::Paragraph),
+            Event::Start(Tag::BlockQuote),
+            Event::ReferenceDefinition("[b]:\n> /v".into()),
+            Event::End(Tag::BlockQuote),
+        ];
+        assert_eq!(expected, events);
+    }
+
+    #[test]
+    fn no_hard_break_in_heading_or_table_cell() {
+        let heading: Vec<_> = Parser::new("# foo  \n").collect();
+        assert_eq!(
+            vec![
+                Event::Start(Tag::Heading(1)),
+                Event::Text("foo".into()),
+                Event::End(Tag::Heading(1)),
+            ],
+            heading
+        );
+
+        let cells: Vec<_> = Parser::new_ext("a|b  \n-|-\nc|d  \n", Options::ENABLE_TABLES)
+            .filter_map(|event| match event {
+                Event::Start(_) | Event::End(_) => None,
+                event => Some(event),
+            })
+            .collect();
+        assert_eq!(
+            vec![
+                Event::Text("a".into()),
+                Event::Text("b".into()),
+                Event::Text("c".into()),
+                Event::Text("d".into()),
+            ],
+            cells
+        );
+    }
+
+    #[test]
+    fn empty_link_destination() {
+        fn link_tag(text: &str) -> Tag<'_> {
+            match Parser::new(text).nth(1) {
+                Some(Event::Start(tag)) => tag,
+                other => panic!("expected link start, got {:?}", other),
+            }
+        }
+        let link = |title| Tag::Link(LinkType::Inline, "".into(), CowStr::Borrowed(title));
+        assert_eq!(link(""), link_tag("[x]()"));
+        assert_eq!(link(""), link_tag("[x](<>)"));
+        assert_eq!(link("t"), link_tag("[x](<> \"t\")"));
+        // without angle brackets, the quoted string is the destination
+        assert_eq!(
+            Tag::Link(LinkType::Inline, "\"t\"".into(), "".into()),
+            link_tag("[x]( \"t\")")
+        );
+    }
+
+    #[test]
+    fn empty_link_text() {
+        fn events(text: &str) -> Vec<(Event<'_>, Range<usize>)> {
+            Parser::new(text).into_offset_iter().collect()
+        }
+
+        let link = Tag::Link(LinkType::Inline, "/x".into(), "".into());
+        let expected = vec![
+            (Event::Start(Tag::Paragraph), 0..0),
+            (Event::Start(link.clone()), 0..6),
+            (Event::End(link), 0..6),
+            (Event::End(Tag::Paragraph), 6..6),
+        ];
+        assert_eq!(expected, events("[](/x)"));
+
+        let image = Tag::Image(LinkType::Inline, "/x".into(), "".into());
+        let expected = vec![
+            (Event::Start(Tag::Paragraph), 0..0),
+            (Event::Start(image.clone()), 0..7),
+            (Event::End(image), 0..7),
+            (Event::End(Tag::Paragraph), 7..7),
+        ];
+        assert_eq!(expected, events("![](/x)"));
+
+        let link = Tag::Link(LinkType::Reference, "/x".into(), "".into());
+        let expected = vec![
+            (Event::Start(Tag::Paragraph), 0..0),
+            (Event::Start(link.clone()), 0..5),
+            (Event::End(link), 0..5),
+            (Event::End(Tag::Paragraph), 5..6),
+        ];
+        assert_eq!(expected, events("[][r]\n\n[r]: /x\n"));
+
+        // an empty label can't be defined, so `[]` is always literal
+        let expected = vec![
+            (Event::Start(Tag::Paragraph), 0..0),
+            (Event::Text("[".into()), 0..1),
+            (Event::Text("]".into()), 1..2),
+            (Event::End(Tag::Paragraph), 2..3),
+        ];
+        assert_eq!(expected, &events("[]\n\n[]: /x\n")[..4]);
+    }
+
+    #[test]
+    fn reference_link_offsets() {
+        let text = "[a][r] [b][] [r]\n\n[r]: /x\n[b]: /y\n";
+        let links: Vec<_> = Parser::new(text)
+            .into_offset_iter()
+            .filter_map(|(event, range)| match event {
+                Event::Start(Tag::Link(..)) => Some(range.start),
+                Event::End(Tag::Link(..)) => Some(range.end),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(vec![0, 6, 7, 12, 13, 16], links);
+    }
+
+    #[test]
+    fn events_are_comparable_and_hashable() {
+        let events: Vec<_> = Parser::new("# *a* [b](/u)\n\n1. `c`\n").collect();
+        let expected = vec![
+            Event::Start(Tag::Heading(1)),
+            Event::Start(Tag::Emphasis),
+            Event::Text("a".into()),
+            Event::End(Tag::Emphasis),
+            Event::Text(" ".into()),
+            Event::Start(Tag::Link(LinkType::Inline, "/u".into(), "".into())),
+            Event::Text("b".into()),
+            Event::End(Tag::Link(LinkType::Inline, "/u".into(), "".into())),
+            Event::End(Tag::Heading(1)),
+            Event::Start(Tag::List(Some(1), '.')),
+            Event::Start(Tag::Item),
+            Event::Code("c".into()),
+            Event::End(Tag::Item),
+            Event::End(Tag::List(Some(1), '.')),
+        ];
+        assert_eq!(expected, events);
+
+        let unique: std::collections::HashSet<_> = events.into_iter().collect();
+        assert!(unique.contains(&Event::Start(Tag::Item)));
+        assert_eq!(14, unique.len());
+    }
+
     // FIXME: add this one regression suite
     #[test]
     fn link_def_at_eof() {
@@ -3033,6 +5941,76 @@ mod test {
         assert_eq!(expected, buf);
     }
 
+    #[test]
+    fn unresolved_reference_callback() {
+        let test_str = "[ok] and [typo][oj] but ![img][missing]\n\n[ok]: /url\n";
+        let unresolved = std::cell::RefCell::new(Vec::new());
+        let callback = |label: &str, range: Range<usize>| {
+            unresolved.borrow_mut().push((label.to_owned(), range));
+        };
+        let parser = Parser::new(test_str).unresolved_reference_callback(&callback);
+        let urls = parser
+            .filter_map(|event| match event {
+                Event::Start(Tag::Link(_, url, _)) => Some(url),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(vec![CowStr::from("/url")], urls);
+        assert_eq!(
+            vec![("oj".to_owned(), 15..19), ("missing".to_owned(), 30..39)],
+            unresolved.into_inner()
+        );
+    }
+
+    #[test]
+    fn shortcut_references_disabled() {
+        let text = "[foo], [foo][], [bar][foo] and [^n]\n\n[foo]: /url\n[^n]: note\n";
+        let mut html = String::new();
+        crate::html::push_html(
+            &mut html,
+            Parser::new_ext(text, Options::ENABLE_FOOTNOTES).shortcut_references(false),
+        );
+        assert!(html.starts_with(
+            "<p>[foo], [foo][], <a href=\"/url\">bar</a> and \
+             <sup class=\"footnote-reference\"><a href=\"#n\">1</a></sup></p>\n"
+        ));
+
+        let links: Vec<_> = Parser::new(text)
+            .filter_map(|event| match event {
+                Event::Start(Tag::Link(link_type, ..)) => Some(link_type),
+                _ => None,
+            })
+            .collect();
+        let expected = vec![LinkType::Shortcut, LinkType::Collapsed, LinkType::Reference];
+        assert_eq!(expected, links);
+    }
+
+    #[test]
+    fn do_not_decode_entities() {
+        let text = "a &amp; &#42; &bogus; [b &copy;](/x?a=1&amp;b=2 \"t&amp;\")\n";
+        let collect = |options| {
+            let mut texts = String::new();
+            let mut link = None;
+            for event in Parser::new_ext(text, options) {
+                match event {
+                    Event::Text(s) => texts.push_str(&s),
+                    Event::Start(Tag::Link(_, dest, title)) => link = Some((dest, title)),
+                    _ => {}
+                }
+            }
+            (texts, link)
+        };
+
+        let (decoded, link) = collect(Options::empty());
+        assert_eq!("a & * &bogus; b ©", decoded);
+        assert_eq!(Some(("/x?a=1&b=2".into(), "t&".into())), link);
+
+        let (preserved, link) = collect(Options::DO_NOT_DECODE_ENTITIES);
+        assert_eq!("a &amp; &#42; &bogus; b &copy;", preserved);
+        assert_eq!(Some(("/x?a=1&b=2".into(), "t&".into())), link);
+    }
+
     #[test]
     fn simple_broken_link_callback() {
         let test_str = "This is a link w/o def: [hello][world]";
@@ -3061,13 +6039,98 @@ mod test {
         assert!(link_tag_count > 0);
     }
 
+    #[test]
+    fn wikilinks() {
+        let events: Vec<_> = Parser::new_ext(
+            "[[Page Name]] [[ Other | text ]]",
+            Options::ENABLE_WIKILINKS,
+        )
+        .collect();
+        let page = Tag::Link(LinkType::WikiLink, "page-name".into(), "".into());
+        let other = Tag::Link(LinkType::WikiLink, "other".into(), "".into());
+        let expected = vec![
+            Event::Start(Tag::Paragraph),
+            Event::Start(page.clone()),
+            Event::Text("Page Name".into()),
+            Event::End(page),
+            Event::Text(" ".into()),
+            Event::Start(other.clone()),
+            Event::Text("text".into()),
+            Event::End(other),
+            Event::End(Tag::Paragraph),
+        ];
+        assert_eq!(expected, events);
+    }
+
+    #[test]
+    fn wikilink_inside_link_text() {
+        let events: Vec<_> =
+            Parser::new_ext("[x [[Page]] y](u)", Options::ENABLE_WIKILINKS).collect();
+        let page = Tag::Link(LinkType::WikiLink, "page".into(), "".into());
+        let expected = vec![
+            Event::Start(Tag::Paragraph),
+            Event::Text("[".into()),
+            Event::Text("x ".into()),
+            Event::Start(page.clone()),
+            Event::Text("Page".into()),
+            Event::End(page),
+            Event::Text(" y".into()),
+            Event::Text("]".into()),
+            Event::Text("(u)".into()),
+            Event::End(Tag::Paragraph),
+        ];
+        assert_eq!(expected, events);
+    }
+
+    #[test]
+    fn wikilink_code_span_precedence() {
+        let events: Vec<_> = Parser::new_ext("[[`]]`", Options::ENABLE_WIKILINKS).collect();
+        let expected = vec![
+            Event::Start(Tag::Paragraph),
+            Event::Text("[[".into()),
+            Event::Code("]]".into()),
+            Event::End(Tag::Paragraph),
+        ];
+        assert_eq!(expected, events);
+    }
+
+    #[test]
+    fn wikilink_destination_callback() {
+        let mut parser = Parser::new_with_broken_link_callback(
+            "[[Page Name|x]]",
+            Options::ENABLE_WIKILINKS,
+            Some(&|norm, raw| {
+                assert_eq!("Page Name", norm);
+                assert_eq!("Page Name", raw);
+                Some(("/wiki/Page_Name".to_owned(), "Page".to_owned()))
+            }),
+        );
+        let link = Tag::Link(LinkType::WikiLink, "/wiki/Page_Name".into(), "Page".into());
+        assert_eq!(Some(Event::Start(link)), parser.nth(1));
+    }
+
+    #[test]
+    fn link_title_no_alloc() {
+        let title = Parser::new("[x](/u \"a 'b' c\")")
+            .find_map(|event| match event {
+                Event::Start(Tag::Link(_, _, title)) => Some(title),
+                _ => None,
+            })
+            .unwrap();
+        if let CowStr::Borrowed(title) = title {
+            assert_eq!("a 'b' c", title);
+        } else {
+            panic!("title should be borrowed from the source");
+        }
+    }
+
     #[test]
     fn code_block_kind_check_fenced() {
         let parser = Parser::new("hello\n```test\ntadam\n```");
         let mut found = 0;
         for (ev, _range) in parser.into_offset_iter() {
             match ev {
-                Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(syntax))) => {
+                Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(syntax, _))) => {
                     assert_eq!(syntax.as_ref(), "test");
                     found += 1;
                 }
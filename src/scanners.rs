@@ -19,12 +19,23 @@
 // THE SOFTWARE.
 
 //! Scanners for fragments of CommonMark syntax
-
-use std::char;
-use std::convert::TryInto;
+//!
+//! Scanners are plain functions over the bytes from the current position. On a
+//! match they return the number of bytes consumed, possibly along with a
+//! value, so callers advance their own index by that amount. Scanners that
+//! can't fail return 0 when nothing matches. None of them carry state, so
+//! composing them is a matter of adding up lengths, and backtracking after a
+//! partial match is done by keeping the index from before the attempt.
+
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::char;
+use core::convert::TryInto;
+use core::ops::Range;
 
 use crate::entities;
-use crate::parse::{Alignment, HtmlScanGuard, LinkType};
+use crate::parse::{AdmonitionKind, Alignment, HtmlScanGuard, LinkType};
 pub use crate::puncttable::{is_ascii_punctuation, is_punctuation};
 use crate::strings::CowStr;
 
@@ -204,9 +215,9 @@ impl<'a> LineStart<'a> {
     /// Return value is the character, the start index, and the indent in spaces.
     /// For ordered list markers, the character will be one of b'.' or b')'. For
     /// bullet list markers, it will be one of b'-', b'+', or b'*'.
-    pub(crate) fn scan_list_marker(&mut self) -> Option<(u8, u64, usize)> {
+    pub(crate) fn scan_list_marker(&mut self, code_indent: usize) -> Option<(u8, u64, usize)> {
         let save = self.clone();
-        let indent = self.scan_space_upto(3);
+        let indent = self.scan_space_upto((code_indent - 1).min(3));
         if self.ix < self.bytes.len() {
             let c = self.bytes[self.ix];
             if c == b'-' || c == b'+' || c == b'*' {
@@ -221,7 +232,7 @@ impl<'a> LineStart<'a> {
                 }
                 self.ix += 1;
                 if self.scan_space(1) || self.is_at_eol() {
-                    return self.finish_list_marker(c, 0, indent + 2);
+                    return self.finish_list_marker(c, 0, indent + 2, code_indent);
                 }
             } else if c >= b'0' && c <= b'9' {
                 let start_ix = self.ix;
@@ -235,7 +246,12 @@ impl<'a> LineStart<'a> {
                     } else if c == b')' || c == b'.' {
                         self.ix = ix;
                         if self.scan_space(1) || self.is_at_eol() {
-                            return self.finish_list_marker(c, val, indent + self.ix - start_ix);
+                            return self.finish_list_marker(
+                                c,
+                                val,
+                                indent + self.ix - start_ix,
+                                code_indent,
+                            );
                         } else {
                             break;
                         }
@@ -254,6 +270,7 @@ impl<'a> LineStart<'a> {
         c: u8,
         start: u64,
         mut indent: usize,
+        code_indent: usize,
     ) -> Option<(u8, u64, usize)> {
         let save = self.clone();
 
@@ -262,8 +279,8 @@ impl<'a> LineStart<'a> {
             return Some((c, start, indent));
         }
 
-        let post_indent = self.scan_space_upto(4);
-        if post_indent < 4 {
+        let post_indent = self.scan_space_upto(code_indent);
+        if post_indent < code_indent {
             indent += post_indent;
         } else {
             *self = save;
@@ -271,6 +288,31 @@ impl<'a> LineStart<'a> {
         Some((c, start, indent))
     }
 
+    /// Returns the kind of an admonition marker like `[!NOTE]` followed only by
+    /// whitespace on its line. Resets itself to original state otherwise.
+    pub(crate) fn scan_admonition_marker(&mut self) -> Option<AdmonitionKind> {
+        let rest = &self.bytes[self.ix..];
+        if !rest.starts_with(b"[!") {
+            return None;
+        }
+        // no marker is longer than `[!IMPORTANT]`, so don't look any further
+        let close = memchr(b']', &rest[..rest.len().min(b"[!IMPORTANT]".len())])?;
+        let name = &rest[2..close];
+        let kind = [
+            (&b"note"[..], AdmonitionKind::Note),
+            (b"tip", AdmonitionKind::Tip),
+            (b"important", AdmonitionKind::Important),
+            (b"warning", AdmonitionKind::Warning),
+            (b"caution", AdmonitionKind::Caution),
+        ]
+        .iter()
+        .find(|(kind_name, _)| name.eq_ignore_ascii_case(kind_name))?
+        .1;
+        scan_blank_line(&rest[close + 1..])?;
+        self.ix += close + 1;
+        Some(kind)
+    }
+
     /// Returns Some(is_checked) when a task list marker was found. Resets itself
     /// to original state otherwise.
     pub(crate) fn scan_task_list_marker(&mut self) -> Option<bool> {
@@ -414,6 +456,37 @@ pub(crate) fn scan_nextline(bytes: &[u8]) -> usize {
     memchr(b'\n', bytes).map_or(bytes.len(), |x| x + 1)
 }
 
+/// Scans front matter at the start of a document: a line of `---` (YAML) or
+/// `+++` (TOML), followed by any lines up to a closing line of the same
+/// delimiter.
+///
+/// Returns the range of the content and the number of bytes scanned.
+pub(crate) fn scan_front_matter(data: &[u8]) -> Option<(Range<usize>, usize)> {
+    let delim: &[u8] = if data.starts_with(b"---") {
+        b"---"
+    } else if data.starts_with(b"+++") {
+        b"+++"
+    } else {
+        return None;
+    };
+    let delim_line = |line: &[u8]| {
+        if !line.starts_with(delim) {
+            return None;
+        }
+        let ix = 3 + scan_whitespace_no_nl(&line[3..]);
+        scan_eol(&line[ix..]).map(|eol| ix + eol)
+    };
+    let content_start = delim_line(data).filter(|&n| n < data.len())?;
+    let mut ix = content_start;
+    while ix < data.len() {
+        if let Some(n) = delim_line(&data[ix..]) {
+            return Some((content_start..ix, ix + n));
+        }
+        ix += scan_nextline(&data[ix..]);
+    }
+    None
+}
+
 // return: end byte for closing code fence, or None
 // if the line is not a closing code fence
 pub(crate) fn scan_closing_code_fence(
@@ -605,6 +678,59 @@ pub(crate) fn scan_code_fence(data: &[u8]) -> Option<(usize, u8)> {
     }
 }
 
+/// Scans Pandoc-style superscript or subscript delimited by `c`, like `^2^` or
+/// `~2~`. The content may not be empty or contain unescaped whitespace, and the
+/// closing delimiter may not be followed by another one.
+///
+/// Returns number of bytes scanned, including both delimiters.
+pub(crate) fn scan_script(data: &[u8], c: u8) -> Option<usize> {
+    let mut i = 1;
+    while i < data.len() {
+        match data[i] {
+            b'\\'
+                if data
+                    .get(i + 1)
+                    .filter(|&&b| b != b'\n' && b != b'\r')
+                    .is_some() =>
+            {
+                i += 1;
+            }
+            b if b == c => {
+                return if i > 1 && data.get(i + 1) != Some(&c) {
+                    Some(i + 1)
+                } else {
+                    None
+                };
+            }
+            b if is_ascii_whitespace(b) => return None,
+            _ => (),
+        }
+        i += 1;
+    }
+    None
+}
+
+/// Scans a wiki link like `[[Page]]` or `[[Page|Display]]`. Wiki links do not
+/// span multiple lines or contain brackets, and the page name may not be blank.
+///
+/// Returns number of bytes scanned, including both delimiters.
+pub(crate) fn scan_wikilink(data: &[u8]) -> Option<usize> {
+    if !data.starts_with(b"[[") {
+        return None;
+    }
+    let content_len = scan_while(&data[2..], |c| {
+        c != b'[' && c != b']' && c != b'\n' && c != b'\r'
+    });
+    let content = &data[2..(2 + content_len)];
+    let page_len = scan_while(content, |c| c != b'|');
+    if !data[(2 + content_len)..].starts_with(b"]]")
+        || content[..page_len].iter().all(|&c| is_ascii_whitespace(c))
+    {
+        return None;
+    }
+    Some(content_len + 4)
+}
+
 pub(crate) fn scan_blockquote_start(data: &[u8]) -> Option<usize> {
     if data.starts_with(b"> ") {
         Some(2)
@@ -627,7 +753,7 @@ pub(crate) fn scan_empty_list(data: &[u8]) -> bool {
 }
 
 // return number of bytes scanned, delimiter, start index, and indent
-pub(crate) fn scan_listitem(bytes: &[u8]) -> Option<(usize, u8, usize, usize)> {
+pub(crate) fn scan_listitem(bytes: &[u8], code_indent: usize) -> Option<(usize, u8, usize, usize)> {
     let mut c = *bytes.get(0)?;
     let (w, start) = match c {
         b'-' | b'+' | b'*' => (1, 0),
@@ -644,11 +770,11 @@ pub(crate) fn scan_listitem(bytes: &[u8]) -> Option<(usize, u8, usize, usize)> {
         }
     };
     // TODO: replace calc_indent with scan_leading_whitespace, for tab correctness
-    let (mut postn, mut postindent) = calc_indent(&bytes[w..], 5);
+    let (mut postn, mut postindent) = calc_indent(&bytes[w..], code_indent.saturating_add(1));
     if postindent == 0 {
         scan_eol(&bytes[w..])?;
         postindent += 1;
-    } else if postindent > 4 {
+    } else if postindent > code_indent {
         postn = 1;
         postindent = 1;
     }
@@ -966,7 +1092,7 @@ pub(crate) fn is_html_tag(tag: &[u8]) -> bool {
                     // We can compare case insensitively because the probes are
                     // all lower case alpha strings.
                     match a.cmp(&(b | 0x20)) {
-                        std::cmp::Ordering::Equal => None,
+                        core::cmp::Ordering::Equal => None,
                         inequality => Some(inequality),
                     }
                 })
@@ -1007,6 +1133,10 @@ pub(crate) fn scan_html_block_inner(
             loop {
                 i += scan_whitespace_no_nl(&data[i..]);
                 if let Some(eol_bytes) = scan_eol(&data[i..]) {
+                    if eol_bytes == 0 {
+                        // End of input, the tag is unterminated.
+                        return None;
+                    }
                     if let Some(handler) = newline_handler {
                         i += eol_bytes;
                         i += handler(&data[i..]);
@@ -1140,6 +1270,113 @@ fn scan_email(text: &str, start_ix: usize) -> Option<(usize, CowStr<'_>)> {
     Some((start_ix + i + 1, text[start_ix..(start_ix + i)].into()))
 }
 
+/// Scans a GFM extended autolink: either `www.` or one of `schemes` followed by
+/// `://`, then a valid domain and an optional path. Trailing punctuation is not
+/// part of the link.
+///
+/// Returns the length of the link and whether it is a `www.` link without a
+/// scheme.
+pub(crate) fn scan_extended_autolink(data: &[u8], schemes: &[&str]) -> Option<(usize, bool)> {
+    let (domain_start, is_www) = if data.starts_with(b"www.") {
+        (0, true)
+    } else {
+        let scheme = schemes.iter().find(|scheme| {
+            let scheme = scheme.as_bytes();
+            data.len() > scheme.len() + 3
+                && data[..scheme.len()].eq_ignore_ascii_case(scheme)
+                && data[scheme.len()..].starts_with(b"://")
+        })?;
+        (scheme.len() + 3, false)
+    };
+    // links with a scheme may have a domain without periods, like `localhost`
+    let mut end = domain_start + scan_autolink_domain(&data[domain_start..], !is_www)?;
+    end += scan_while(&data[end..], |c| !is_ascii_whitespace(c) && c != b'<');
+    let min_end = if is_www { 4 } else { domain_start };
+
+    loop {
+        match data[end - 1] {
+            b'?' | b'!' | b'.' | b',' | b':' | b'*' | b'_' | b'~' => end -= 1,
+            b')' => {
+                // only strip closing parentheses without a matching opening one
+                let link = &data[..end];
+                let opening = link.iter().filter(|&&c| c == b'(').count();
+                let closing = link.iter().filter(|&&c| c == b')').count();
+                if closing <= opening {
+                    break;
+                }
+                end -= 1;
+            }
+            b';' => {
+                // strip something resembling an entity reference, like `&amp;`
+                let name_len = scan_rev_while(&data[..(end - 1)], is_ascii_alphanumeric);
+                if name_len == 0 || end < name_len + 2 || data[end - name_len - 2] != b'&' {
+                    break;
+                }
+                end -= name_len + 2;
+            }
+            _ => break,
+        }
+        if end <= min_end {
+            return None;
+        }
+    }
+
+    Some((end, is_www))
+}
+
+/// Scans a bare email address for GFM extended autolinks: alphanumerics and
+/// `.+-_`, followed by `@` and a domain with at least one period. A trailing
+/// period is not part of the address, and the address may not end in `-` or `_`.
+///
+/// Returns the length of the address.
+pub(crate) fn scan_extended_email(data: &[u8]) -> Option<usize> {
+    let local_len = scan_while(data, |c| is_ascii_alphanumeric(c) || b".+-_".contains(&c));
+    if local_len == 0 || data.get(local_len) != Some(&b'@') {
+        return None;
+    }
+    let domain_start = local_len + 1;
+    let mut end = domain_start
+        + scan_while(&data[domain_start..], |c| {
+            is_ascii_alphanumeric(c) || c == b'.' || c == b'-' || c == b'_'
+        });
+    end -= scan_rev_while(&data[domain_start..end], |c| c == b'.');
+    let domain = &data[domain_start..end];
+    match domain.last() {
+        Some(b'-') | Some(b'_') | None => None,
+        Some(_) if domain[0] != b'.' && domain.contains(&b'.') => Some(end),
+        Some(_) => None,
+    }
+}
+
+/// Scans the domain of an extended autolink: segments of alphanumerics,
+/// underscores and hyphens separated by periods. The last two segments may not
+/// contain underscores.
+fn scan_autolink_domain(data: &[u8], allow_short: bool) -> Option<usize> {
+    let mut periods = 0;
+    // whether the previous and the current segment contain an underscore
+    let mut underscores = (false, false);
+    let mut i = 0;
+    while i < data.len() {
+        match data[i] {
+            b'.' => {
+                periods += 1;
+                underscores = (underscores.1, false);
+            }
+            b'_' => underscores.1 = true,
+            c if is_ascii_alphanumeric(c) || c == b'-' => (),
+            _ => break,
+        }
+        i += 1;
+    }
+
+    if i == 0 || data[0] == b'.' || underscores.0 || underscores.1 || (periods == 0 && !allow_short)
+    {
+        None
+    } else {
+        Some(i)
+    }
+}
+
 /// Scan comment, declaration, or CDATA section, with initial "<!" already consumed.
 /// Returns byte offset on match.
 pub(crate) fn scan_inline_html_comment(
@@ -1232,13 +1469,81 @@ mod test {
     use super::*;
     #[test]
     fn overflow_list() {
-        assert!(
-            scan_listitem(b"4444444444444444444444444444444444444444444444444444444444!").is_none()
-        );
+        assert!(scan_listitem(
+            b"4444444444444444444444444444444444444444444444444444444444!",
+            4
+        )
+        .is_none());
     }
 
     #[test]
     fn overflow_by_addition() {
-        assert!(scan_listitem(b"1844674407370955161615!").is_none());
+        assert!(scan_listitem(b"1844674407370955161615!", 4).is_none());
+    }
+
+    #[test]
+    fn extended_autolink() {
+        let schemes = &["http", "https"];
+        let scan = |s: &str| scan_extended_autolink(s.as_bytes(), schemes);
+        assert_eq!(Some((15, true)), scan("www.example.com."));
+        assert_eq!(Some((18, false)), scan("HTTPS://localhost/ rest"));
+        assert_eq!(Some((14, false)), scan("http://a.b/(c)))"));
+        assert_eq!(Some((17, false)), scan("http://a.b/?q=1&x&amp;"));
+        assert_eq!(Some((12, false)), scan("http://a.b/c<d>"));
+        assert_eq!(None, scan("www.a_b.c_d"));
+        assert_eq!(None, scan("www. a"));
+        assert_eq!(None, scan("localhost"));
+        assert_eq!(None, scan("ftp://a.b"));
+        assert_eq!(None, scan("http://"));
+    }
+
+    #[test]
+    fn extended_email() {
+        let scan = |s: &str| scan_extended_email(s.as_bytes());
+        assert_eq!(Some(11), scan("me@here.com"));
+        assert_eq!(Some(17), scan("a.b-c_d+e@x.y.com. rest"));
+        assert_eq!(None, scan("a@b"));
+        assert_eq!(None, scan("a@b."));
+        assert_eq!(None, scan("a@.b"));
+        assert_eq!(None, scan("a@b.c-"));
+        assert_eq!(None, scan("a@b.c_"));
+        assert_eq!(None, scan("@b.c"));
+        assert_eq!(None, scan("a!@b.c"));
+    }
+
+    #[test]
+    fn composed_scanner() {
+        // a custom scanner for `{dest}` built from existing ones
+        fn scan_braced_dest(data: &str) -> Option<(usize, &str)> {
+            let bytes = data.as_bytes();
+            let mut ix = scan_ch(bytes, b'{');
+            if ix == 0 {
+                return None;
+            }
+            ix += scan_whitespace_no_nl(&bytes[ix..]);
+            let (dest_len, dest) = scan_link_dest(data, ix, 0)?;
+            ix += dest_len;
+            ix += scan_whitespace_no_nl(&bytes[ix..]);
+            // on failure, the caller's index is left where it was
+            if scan_ch(&bytes[ix..], b'}') == 0 {
+                return None;
+            }
+            Some((ix + 1, dest))
+        }
+
+        assert_eq!(Some((8, "/url")), scan_braced_dest("{ /url }rest"));
+        assert_eq!(Some((7, "a b")), scan_braced_dest("{<a b>}"));
+        assert_eq!(None, scan_braced_dest("{/url rest}"));
+        assert_eq!(None, scan_braced_dest("/url}"));
+    }
+
+    #[test]
+    fn closing_code_fence_trailing_spaces() {
+        assert_eq!(Some(3), scan_closing_code_fence(b"```\n", b'`', 3));
+        assert_eq!(Some(6), scan_closing_code_fence(b"```   \n", b'`', 3));
+        assert_eq!(Some(7), scan_closing_code_fence(b"~~~~   ", b'~', 3));
+        assert_eq!(None, scan_closing_code_fence(b"``` foo\n", b'`', 3));
+        assert_eq!(None, scan_closing_code_fence(b"```foo\n", b'`', 3));
+        assert_eq!(None, scan_closing_code_fence(b"``\n", b'`', 3));
     }
 }
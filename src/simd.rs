@@ -19,7 +19,7 @@
 use crate::parse::LoopInstruction;
 use core::arch::x86_64::*;
 
-const VECTOR_SIZE: usize = std::mem::size_of::<__m128i>();
+const VECTOR_SIZE: usize = core::mem::size_of::<__m128i>();
 
 /// Generates a lookup table containing the bitmaps for our
 /// special marker bytes. This is effectively a 128 element 2d bitvector,
@@ -40,6 +40,8 @@ const fn compute_lookup() -> [u8; 16] {
     lookup[(b'<' & 0x0f) as usize] |= 1 << (b'<' >> 4);
     lookup[(b'!' & 0x0f) as usize] |= 1 << (b'!' >> 4);
     lookup[(b'`' & 0x0f) as usize] |= 1 << (b'`' >> 4);
+    lookup[(b'$' & 0x0f) as usize] |= 1 << (b'$' >> 4);
+    lookup[(b'^' & 0x0f) as usize] |= 1 << (b'^' >> 4);
     lookup
 }
 
@@ -151,7 +153,7 @@ where
         let mask = compute_mask(bytes, ix);
         let block_start = ix;
         ix = match process_mask(mask, bytes, ix, &mut callback) {
-            Ok(ix) => std::cmp::max(ix, VECTOR_SIZE + block_start),
+            Ok(ix) => core::cmp::max(ix, VECTOR_SIZE + block_start),
             Err((end_ix, val)) => return (end_ix, val),
         };
     }
@@ -210,7 +212,8 @@ mod simd_test {
     #[test]
     fn exhaustive_search() {
         let chars = [
-            b'\n', b'\r', b'*', b'_', b'~', b'|', b'&', b'\\', b'[', b']', b'<', b'!', b'`',
+            b'\n', b'\r', b'*', b'_', b'~', b'|', b'&', b'\\', b'[', b']', b'<', b'!', b'`', b'$',
+            b'^',
         ];
 
         for &c in &chars {
@@ -1,11 +1,14 @@
-use std::borrow::{Borrow, ToOwned};
-use std::convert::{AsRef, TryFrom};
-use std::fmt;
-use std::hash::{Hash, Hasher};
-use std::ops::Deref;
-use std::str::from_utf8;
-
-const MAX_INLINE_STR_LEN: usize = 3 * std::mem::size_of::<isize>() - 1;
+use alloc::borrow::ToOwned;
+use alloc::boxed::Box;
+use alloc::string::String;
+use core::borrow::Borrow;
+use core::convert::{AsRef, TryFrom};
+use core::fmt;
+use core::hash::{Hash, Hasher};
+use core::ops::Deref;
+use core::str::from_utf8;
+
+const MAX_INLINE_STR_LEN: usize = 3 * core::mem::size_of::<isize>() - 1;
 
 /// Returned when trying to convert a `&str` into a `InlineStr`
 /// but it fails because it doesn't fit.
@@ -40,7 +43,7 @@ impl From<char> for InlineStr {
     }
 }
 
-impl<'a> std::cmp::PartialEq<InlineStr> for InlineStr {
+impl<'a> core::cmp::PartialEq<InlineStr> for InlineStr {
     fn eq(&self, other: &InlineStr) -> bool {
         self.deref() == other.deref()
     }
@@ -103,7 +106,7 @@ impl<'a> Hash for CowStr<'a> {
     }
 }
 
-impl<'a> std::clone::Clone for CowStr<'a> {
+impl<'a> core::clone::Clone for CowStr<'a> {
     fn clone(&self) -> Self {
         match self {
             CowStr::Boxed(s) => match InlineStr::try_from(&**s) {
@@ -116,7 +119,7 @@ impl<'a> std::clone::Clone for CowStr<'a> {
     }
 }
 
-impl<'a> std::cmp::PartialEq<CowStr<'a>> for CowStr<'a> {
+impl<'a> core::cmp::PartialEq<CowStr<'a>> for CowStr<'a> {
     fn eq(&self, other: &CowStr) -> bool {
         self.deref() == other.deref()
     }
@@ -166,6 +169,19 @@ impl<'a> CowStr<'a> {
             CowStr::Inlined(s) => s.deref().to_owned(),
         }
     }
+
+    /// Converts into a `CowStr` that owns its content, copying it if it was
+    /// borrowed.
+    pub fn into_static(self) -> CowStr<'static> {
+        match self {
+            CowStr::Boxed(b) => CowStr::Boxed(b),
+            CowStr::Borrowed(b) => match InlineStr::try_from(b) {
+                Ok(inline) => CowStr::Inlined(inline),
+                Err(..) => CowStr::Boxed(b.into()),
+            },
+            CowStr::Inlined(s) => CowStr::Inlined(s),
+        }
+    }
 }
 
 impl<'a> fmt::Display for CowStr<'a> {
@@ -240,4 +256,22 @@ mod test_special_string {
             panic!("Expected a Inlined variant!");
         }
     }
+
+    #[test]
+    #[cfg(target_pointer_width = "64")]
+    fn borrowed_str_into_static() {
+        let short = CowStr::Borrowed("0123456789abcde").into_static();
+        if let CowStr::Inlined(..) = short {
+        } else {
+            panic!("Expected a Inlined variant!");
+        }
+
+        let s = "0123456789abcdefghijklm";
+        let long = CowStr::Borrowed(s).into_static();
+        if let CowStr::Boxed(..) = long {
+        } else {
+            panic!("Expected a Boxed variant!");
+        }
+        assert_eq!(s, &*long);
+    }
 }
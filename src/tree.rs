@@ -6,8 +6,9 @@
 
 //! A Vec-based container for a tree structure.
 
-use std::num::NonZeroUsize;
-use std::ops::{Add, Sub};
+use alloc::vec::Vec;
+use core::num::NonZeroUsize;
+use core::ops::{Add, Sub};
 
 #[derive(Debug, Eq, PartialEq, Copy, Clone)]
 pub enum TreePointer {
@@ -169,10 +170,25 @@ impl<T: Default> Tree<T> {
     }
 
     /// Walks the spine from a root node up to, but not including, the current node.
-    pub fn walk_spine(&self) -> impl std::iter::DoubleEndedIterator<Item = &TreeIndex> {
+    pub fn walk_spine(&self) -> impl core::iter::DoubleEndedIterator<Item = &TreeIndex> {
         self.spine.iter()
     }
 
+    /// Sets the focus and the spine leading up to it.
+    pub fn restore_cursor(&mut self, (cur, spine): (TreePointer, Vec<TreeIndex>)) {
+        self.cur = cur;
+        self.spine = spine;
+    }
+
+    /// Replaces the node in focus, which must be the first child of its parent,
+    /// with the given node and moves focus to it.
+    pub fn replace_cur(&mut self, ix: TreeIndex) {
+        if let Some(&parent) = self.spine.last() {
+            self[parent].child = TreePointer::Valid(ix);
+        }
+        self.cur = TreePointer::Valid(ix);
+    }
+
     /// Moves focus to the next sibling of the given node.
     pub fn next_sibling(&mut self, cur_ix: TreeIndex) -> TreePointer {
         self.cur = self[cur_ix].next;
@@ -180,19 +196,19 @@ impl<T: Default> Tree<T> {
     }
 }
 
-impl<T> std::fmt::Debug for Tree<T>
+impl<T> core::fmt::Debug for Tree<T>
 where
-    T: std::fmt::Debug,
+    T: core::fmt::Debug,
 {
-    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
         fn debug_tree<T>(
             tree: &Tree<T>,
             cur: TreeIndex,
             indent: usize,
-            f: &mut std::fmt::Formatter,
-        ) -> std::fmt::Result
+            f: &mut core::fmt::Formatter,
+        ) -> core::fmt::Result
         where
-            T: std::fmt::Debug,
+            T: core::fmt::Debug,
         {
             for _ in 0..indent {
                 write!(f, "  ")?;
@@ -216,7 +232,7 @@ where
     }
 }
 
-impl<T> std::ops::Index<TreeIndex> for Tree<T> {
+impl<T> core::ops::Index<TreeIndex> for Tree<T> {
     type Output = Node<T>;
 
     fn index(&self, ix: TreeIndex) -> &Self::Output {
@@ -224,7 +240,7 @@ impl<T> std::ops::Index<TreeIndex> for Tree<T> {
     }
 }
 
-impl<T> std::ops::IndexMut<TreeIndex> for Tree<T> {
+impl<T> core::ops::IndexMut<TreeIndex> for Tree<T> {
     fn index_mut(&mut self, ix: TreeIndex) -> &mut Node<T> {
         self.nodes.index_mut(ix.get())
     }
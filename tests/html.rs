@@ -209,6 +209,264 @@ fn html_test_10() {
     assert_eq!(expected, s);
 }
 
+#[test]
+fn html_test_11() {
+    let original = "Euler: $e^{i\\pi} + 1 = 0$\n$$\na < b\n$$\n";
+    let expected = r##"<p>Euler: <span class="math math-inline">e^{i\pi} + 1 = 0</span></p>
+<div class="math math-display">
+a &lt; b
+</div>
+"##;
+
+    let mut s = String::new();
+    let mut opts = Options::empty();
+    opts.insert(Options::ENABLE_MATH);
+    html::push_html(&mut s, Parser::new_ext(original, opts));
+    assert_eq!(expected, s);
+}
+
+#[test]
+fn html_test_12() {
+    let original = "Visit www.example.com/a_b?c=(d). or *http://x.io/y*, not `http://z.io`.\n";
+    let expected = r##"<p>Visit <a href="http://www.example.com/a_b?c=(d)">www.example.com/a_b?c=(d)</a>. or <em><a href="http://x.io/y">http://x.io/y</a></em>, not <code>http://z.io</code>.</p>
+"##;
+
+    let mut s = String::new();
+    let mut opts = Options::empty();
+    opts.insert(Options::ENABLE_GFM_AUTOLINKS);
+    html::push_html(&mut s, Parser::new_ext(original, opts));
+    assert_eq!(expected, s);
+}
+
+#[test]
+fn html_test_13() {
+    let original = "ftp://x and https://y\n";
+    let expected_default = r##"<p>ftp://x and <a href="https://y">https://y</a></p>
+"##;
+    let expected_ftp = r##"<p><a href="ftp://x">ftp://x</a> and https://y</p>
+"##;
+
+    let mut s = String::new();
+    let mut opts = Options::empty();
+    opts.insert(Options::ENABLE_GFM_AUTOLINKS);
+    html::push_html(&mut s, Parser::new_ext(original, opts));
+    assert_eq!(expected_default, s);
+
+    s.clear();
+    let parser = Parser::new_ext(original, opts).autolink_schemes(&["ftp"]);
+    html::push_html(&mut s, parser);
+    assert_eq!(expected_ftp, s);
+}
+
+#[test]
+fn html_test_14() {
+    let original = "[[Main Page]], [[Help|how to *edit*]] and `[[code]]`\n";
+    let expected = r##"<p><a href="main-page">Main Page</a>, <a href="help">how to *edit*</a> and <code>[[code]]</code></p>
+"##;
+
+    let mut s = String::new();
+    let mut opts = Options::empty();
+    opts.insert(Options::ENABLE_WIKILINKS);
+    html::push_html(&mut s, Parser::new_ext(original, opts));
+    assert_eq!(expected, s);
+}
+
+#[test]
+fn html_test_15() {
+    let original = "first line\nsecond line\n";
+    let expected_newline = "<p>first line\nsecond line</p>\n";
+    let expected_space = "<p>first line second line</p>\n";
+
+    let mut s = String::new();
+    html::push_html(&mut s, Parser::new(original));
+    assert_eq!(expected_newline, s);
+
+    s.clear();
+    html::HtmlRenderer::new()
+        .soft_break_as_space(true)
+        .push_html(&mut s, Parser::new(original));
+    assert_eq!(expected_space, s);
+}
+
+#[test]
+fn html_test_16() {
+    let original = "E=mc^2^ and H~2~O, but ~~not~~ ^a b^\n";
+    let expected = "<p>E=mc<sup>2</sup> and H<sub>2</sub>O, but <del>not</del> ^a b^</p>\n";
+
+    let mut opts = Options::empty();
+    opts.insert(Options::ENABLE_STRIKETHROUGH);
+    opts.insert(Options::ENABLE_SUPERSCRIPT);
+    opts.insert(Options::ENABLE_SUBSCRIPT);
+    let mut s = String::new();
+    html::push_html(&mut s, Parser::new_ext(original, opts));
+    assert_eq!(expected, s);
+
+    s.clear();
+    html::push_html(&mut s, Parser::new(original));
+    assert_eq!("<p>E=mc^2^ and H~2~O, but ~~not~~ ^a b^</p>\n", s);
+}
+
+#[test]
+fn html_test_17() {
+    let original = "  code\n\npara\n  continued\n\n- item\n\n  item para\n";
+    let expected = "<pre><code>code\n</code></pre>\n<p>para\ncontinued</p>\n\
+                    <ul>\n<li>\n<p>item</p>\n<p>item para</p>\n</li>\n</ul>\n";
+
+    let mut s = String::new();
+    html::push_html(
+        &mut s,
+        Parser::new_with_code_indent(original, Options::empty(), 2),
+    );
+    assert_eq!(expected, s);
+}
+
+#[test]
+fn html_test_18() {
+    let original = "    not code\n\n        code\n\n          more\n";
+    let expected = "<p>not code</p>\n<pre><code>code\n\n  more\n</code></pre>\n";
+
+    let mut s = String::new();
+    html::push_html(
+        &mut s,
+        Parser::new_with_code_indent(original, Options::empty(), 8),
+    );
+    assert_eq!(expected, s);
+
+    s.clear();
+    html::push_html(
+        &mut s,
+        Parser::new_with_code_indent(original, Options::empty(), std::usize::MAX),
+    );
+    assert_eq!("<p>not code</p>\n<p>code</p>\n<p>more</p>\n", s);
+}
+
+#[test]
+fn html_test_19() {
+    let original = "contact me@here.com or a@b, not <x@y.com> twice\n";
+    let expected = "<p>contact <a href=\"mailto:me@here.com\">me@here.com</a> or a@b, \
+                    not <a href=\"mailto:x@y.com\">x@y.com</a> twice</p>\n";
+
+    let mut opts = Options::empty();
+    opts.insert(Options::ENABLE_GFM_AUTOLINKS);
+    opts.insert(Options::ENABLE_GFM_AUTOLINK_EMAIL_WITHOUT_MAILTO);
+    let mut s = String::new();
+    html::push_html(&mut s, Parser::new_ext(original, opts));
+    assert_eq!(expected, s);
+
+    s.clear();
+    html::push_html(
+        &mut s,
+        Parser::new_ext(original, Options::ENABLE_GFM_AUTOLINKS),
+    );
+    assert_eq!(
+        "<p>contact me@here.com or a@b, not <a href=\"mailto:x@y.com\">x@y.com</a> twice</p>\n",
+        s
+    );
+}
+
+#[test]
+fn html_test_20() {
+    let original = "# Intro\n\nIntro\n=====\n\n## Intro\n\n### Intro-1 & `code`!\n";
+    let expected = "<h1 id=\"intro\">Intro</h1>\n<h1 id=\"intro-1\">Intro</h1>\n\
+                    <h2 id=\"intro-2\">Intro</h2>\n\
+                    <h3 id=\"intro-1--code\">Intro-1 &amp; <code>code</code>!</h3>\n";
+
+    let mut s = String::new();
+    html::HtmlRenderer::new()
+        .heading_ids(true)
+        .push_html(&mut s, Parser::new(original));
+    assert_eq!(expected, s);
+}
+
+#[test]
+fn html_test_21() {
+    let original = "> [!WARNING]\n> Mind the *gap*.\n\n> [!NOTE] not an alert\n";
+    let expected = "<blockquote class=\"markdown-alert-warning\">\n\
+                    <p>Mind the <em>gap</em>.</p>\n</blockquote>\n\
+                    <blockquote>\n<p>[!NOTE] not an alert</p>\n</blockquote>\n";
+
+    let mut s = String::new();
+    html::push_html(
+        &mut s,
+        Parser::new_ext(original, Options::ENABLE_ADMONITIONS),
+    );
+    assert_eq!(expected, s);
+}
+
+#[test]
+fn html_test_22() {
+    let original = "||hidden *text*|| and a | b || c\n";
+    let expected = "<p><span class=\"spoiler\">hidden <em>text</em></span> and a | b || c</p>\n";
+
+    let mut s = String::new();
+    html::push_html(&mut s, Parser::new_ext(original, Options::ENABLE_SPOILER));
+    assert_eq!(expected, s);
+
+    s.clear();
+    html::push_html(&mut s, Parser::new(original));
+    assert_eq!("<p>||hidden <em>text</em>|| and a | b || c</p>\n", s);
+}
+
+#[test]
+fn html_test_23() {
+    let original = "==marked *text*== and a = b\n";
+    let expected = "<p><mark>marked <em>text</em></mark> and a = b</p>\n";
+
+    let mut s = String::new();
+    html::push_html(&mut s, Parser::new_ext(original, Options::ENABLE_HIGHLIGHT));
+    assert_eq!(expected, s);
+
+    s.clear();
+    html::push_html(&mut s, Parser::new(original));
+    assert_eq!("<p>==marked <em>text</em>== and a = b</p>\n", s);
+}
+
+#[test]
+fn html_test_24() {
+    let original = "- item\n\n    code\n\n-   code\n";
+    let expected = "<ul>\n<li>\n<p>item</p>\n<pre><code>code\n</code></pre>\n</li>\n\
+                    <li>\n<pre><code>code\n</code></pre>\n</li>\n</ul>\n";
+
+    let mut s = String::new();
+    html::push_html(
+        &mut s,
+        Parser::new_with_code_indent(original, Options::empty(), 2),
+    );
+    assert_eq!(expected, s);
+
+    s.clear();
+    html::push_html(
+        &mut s,
+        Parser::new_with_code_indent("-     not code\n", Options::empty(), std::usize::MAX),
+    );
+    assert_eq!("<ul>\n<li>not code</li>\n</ul>\n", s);
+
+    s.clear();
+    let callback = |_: &str, _: &str| Some(("/url".to_owned(), "".to_owned()));
+    html::push_html(
+        &mut s,
+        Parser::new_with_code_indent_and_broken_link_callback(
+            "  [link]\n",
+            Options::empty(),
+            2,
+            Some(&callback),
+        ),
+    );
+    assert_eq!("<pre><code>[link]\n</code></pre>\n", s);
+
+    s.clear();
+    html::push_html(
+        &mut s,
+        Parser::new_with_code_indent_and_broken_link_callback(
+            " [link]\n",
+            Options::empty(),
+            2,
+            Some(&callback),
+        ),
+    );
+    assert_eq!("<p><a href=\"/url\">link</a></p>\n", s);
+}
+
 // TODO: add broken link callback feature
 /*
 #[test]
@@ -0,0 +1,75 @@
+// Checks that the HTML renderer writes output as events arrive, without
+// accumulating state that grows with the size of the document.
+
+extern crate pulldown_cmark;
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use pulldown_cmark::{html, Event, Tag};
+
+struct CountingAlloc;
+
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let now = ALLOCATED.fetch_add(layout.size(), Ordering::SeqCst) + layout.size();
+        // The test is single-threaded, so a plain load and store is enough.
+        if now > PEAK.load(Ordering::SeqCst) {
+            PEAK.store(now, Ordering::SeqCst);
+        }
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        ALLOCATED.fetch_sub(layout.size(), Ordering::SeqCst);
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+/// Discards its input, counting the number of bytes written.
+struct CountingSink(usize);
+
+impl Write for CountingSink {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0 += buf.len();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn render_huge_document_in_bounded_memory() {
+    let blocks = 200_000;
+    let events = (0..blocks).flat_map(|i| {
+        let item = vec![
+            Event::Start(Tag::BlockQuote),
+            Event::Start(Tag::Paragraph),
+            Event::Text("lorem ".into()),
+            Event::Start(Tag::Emphasis),
+            Event::Text(format!("ipsum {}", i).into()),
+            Event::End(Tag::Emphasis),
+            Event::End(Tag::Paragraph),
+            Event::End(Tag::BlockQuote),
+        ];
+        item.into_iter()
+    });
+
+    let mut sink = CountingSink(0);
+    let baseline = ALLOCATED.load(Ordering::SeqCst);
+    PEAK.store(baseline, Ordering::SeqCst);
+    html::write_html(&mut sink, events).unwrap();
+    let peak = PEAK.load(Ordering::SeqCst) - baseline;
+
+    assert!(sink.0 > 10_000_000);
+    assert!(peak < 64 * 1024, "renderer used {} bytes", peak);
+}
@@ -847,3 +847,2096 @@ fn regression_test_61() {
 
     test_markdown_html(original, expected);
 }
+
+#[test]
+fn regression_test_62() {
+    let original = r##"[x](/u "a 'b' c")
+"##;
+    let expected = r##"<p><a href="/u" title="a 'b' c">x</a></p>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_63() {
+    let original = r##"[x](/u '(a)')
+"##;
+    let expected = r##"<p><a href="/u" title="(a)">x</a></p>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_64() {
+    let original = r##"[x](/u "oops)
+"##;
+    let expected = r##"<p>[x](/u &quot;oops)</p>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_65() {
+    let original = r##"[x](/u "oops)
+
+[x]: /v
+"##;
+    let expected = r##"<p><a href="/v">x</a>(/u &quot;oops)</p>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_66() {
+    let original = r##"a ** b **** c ****** d
+"##;
+    let expected = r##"<p>a ** b **** c ****** d</p>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_67() {
+    let original = r##"x********y *z*
+"##;
+    let expected = r##"<p>x********y <em>z</em></p>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_68() {
+    let original = r##"__ ____ ______ __a__
+"##;
+    let expected = r##"<p>__ ____ ______ <strong>a</strong></p>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_69() {
+    let original = r##"[a `]` b](x)
+"##;
+    let expected = r##"<p><a href="x">a <code>]</code> b</a></p>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_70() {
+    let original = r##"[a <span title="]">b</span>](x)
+"##;
+    let expected = r##"<p><a href="x">a <span title="]">b</span></a></p>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_71() {
+    let original = r##"[`code]`](u)
+"##;
+    let expected = r##"<p><a href="u"><code>code]</code></a></p>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_72() {
+    let original = r##"[a `b](x)`
+"##;
+    let expected = r##"<p>[a <code>b](x)</code></p>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_73() {
+    let original = r##"[a <i title="](x)">b
+"##;
+    let expected = r##"<p>[a <i title="](x)">b</p>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_74() {
+    let original = r##"  ~~~
+ aaa
+    aaa
+aaa
+   ~~~
+"##;
+    let expected = r##"<pre><code>aaa
+  aaa
+aaa
+</code></pre>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_75() {
+    let original = r##">   ```
+>  a
+>    b
+> ```
+"##;
+    let expected = r##"<blockquote>
+<pre><code>a
+ b
+</code></pre>
+</blockquote>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_76() {
+    let original = r##"- x
+
+    ```
+   a
+     b
+    ```
+"##;
+    let expected = r##"<ul>
+<li>
+<p>x</p>
+<pre><code>a
+ b
+</code></pre>
+</li>
+</ul>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_77() {
+    let original = r##"  ```
+	aaa
+  ```
+"##;
+    let expected = r##"<pre><code>  aaa
+</code></pre>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_78() {
+    let original = r##"text
+2. x
+"##;
+    let expected = r##"<p>text
+2. x</p>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_79() {
+    let original = r##"text
+1. x
+"##;
+    let expected = r##"<p>text</p>
+<ol>
+<li>x</li>
+</ol>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_80() {
+    let original = r##"text
+1)
+"##;
+    let expected = r##"<p>text
+1)</p>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_81() {
+    let original = r##"text
+- x
+"##;
+    let expected = r##"<p>text</p>
+<ul>
+<li>x</li>
+</ul>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_82() {
+    let original = r##"text
++ x
+"##;
+    let expected = r##"<p>text</p>
+<ul>
+<li>x</li>
+</ul>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_83() {
+    let original = r##"text
++
+"##;
+    let expected = r##"<p>text
++</p>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_84() {
+    let original = r##"a < b << c <<http://x> <
+<em>d</em>
+"##;
+    let expected = r##"<p>a &lt; b &lt;&lt; c &lt;<a href="http://x">http://x</a> &lt;
+<em>d</em></p>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_85() {
+    let original = r##">>nested
+"##;
+    let expected = r##"<blockquote>
+<blockquote>
+<p>nested</p>
+</blockquote>
+</blockquote>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_86() {
+    let original = r##"> > nested
+"##;
+    let expected = r##"<blockquote>
+<blockquote>
+<p>nested</p>
+</blockquote>
+</blockquote>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_87() {
+    let original = r##">>>x
+"##;
+    let expected = r##"<blockquote>
+<blockquote>
+<blockquote>
+<p>x</p>
+</blockquote>
+</blockquote>
+</blockquote>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_88() {
+    let original = r##"> >> x
+>> > y
+"##;
+    let expected = r##"<blockquote>
+<blockquote>
+<blockquote>
+<p>x
+y</p>
+</blockquote>
+</blockquote>
+</blockquote>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_89() {
+    let original = r##">  > x
+"##;
+    let expected = r##"<blockquote>
+<blockquote>
+<p>x</p>
+</blockquote>
+</blockquote>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_90() {
+    let original = r##">      x
+"##;
+    let expected = r##"<blockquote>
+<pre><code> x
+</code></pre>
+</blockquote>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_91() {
+    let original = r##">     x
+> y
+"##;
+    let expected = r##"<blockquote>
+<pre><code>x
+</code></pre>
+<p>y</p>
+</blockquote>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_92() {
+    let original = r##">    x
+"##;
+    let expected = r##"<blockquote>
+<p>x</p>
+</blockquote>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_93() {
+    let original = r##">	  x
+"##;
+    let expected = r##"<blockquote>
+<pre><code>x
+</code></pre>
+</blockquote>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_94() {
+    let original = r##"[x](ü/é "tïtle") [y](<ö ü> (ä))
+"##;
+    let expected = r##"<p><a href="%C3%BC/%C3%A9" title="tïtle">x</a> <a href="%C3%B6%20%C3%BC" title="ä">y</a></p>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_95() {
+    let original = r##"a <span title="ü">ö</span> [é](<ü>)<ü-x>
+"##;
+    let expected = r##"<p>a <span title="ü">ö</span> <a href="%C3%BC">é</a>&lt;ü-x&gt;</p>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_96() {
+    let original = r##"[ä]: ü/é
+  "tï
+tle"
+
+[ä] <a title="é">ö</a>[ä](ö)
+"##;
+    let expected = r##"<p><a href="%C3%BC/%C3%A9" title="tï
+tle">ä</a> <a title="é">ö</a><a href="%C3%B6">ä</a></p>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_97() {
+    let original = r##"[xxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxx]: /u
+
+[xxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxx]
+"##;
+    let expected = r##"<p>[xxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxx]: /u</p>
+<p>[xxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxx]</p>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_98() {
+    let original = r##"## ##
+###### ######
+# #
+#     #
+# #########
+"##;
+    let expected = r##"<h2></h2>
+<h6></h6>
+<h1></h1>
+<h1></h1>
+<h1></h1>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_99() {
+    let original = r##"#	#	
+# a	#
+## ## b
+"##;
+    let expected = r##"<h1></h1>
+<h1>a</h1>
+<h2>## b</h2>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_100() {
+    let original = r##"\**bold?**
+"##;
+    let expected = r##"<p>*<em>bold?</em>*</p>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_101() {
+    let original = r##"a\*b \***a** *a\**
+"##;
+    let expected = r##"<p>a*b *<strong>a</strong> <em>a*</em></p>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_102() {
+    let original = r##"\\*a*
+"##;
+    let expected = r##"<p>\<em>a</em></p>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_103() {
+    let original = r##"[a[b](c)
+"##;
+    let expected = r##"<p>[a<a href="c">b</a></p>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_104() {
+    let original = r##"[a [b [c](d)](e)
+"##;
+    let expected = r##"<p>[a [b <a href="d">c</a>](e)</p>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_105() {
+    let original = r##"[foo [bar](/uri)](/uri)
+"##;
+    let expected = r##"<p>[foo <a href="/uri">bar</a>](/uri)</p>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_106() {
+    let original = r##"[foo *[bar [baz](/uri)](/uri)*](/uri)
+"##;
+    let expected = r##"<p>[foo <em>[bar <a href="/uri">baz</a>](/uri)</em>](/uri)</p>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_107() {
+    let original = r##"![[[foo](uri1)](uri2)](uri3)
+"##;
+    let expected = r##"<p><img src="uri3" alt="[foo](uri2)" /></p>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_108() {
+    let original = r##"    a
+
+    b
+"##;
+    let expected = r##"<pre><code>a
+
+b
+</code></pre>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_109() {
+    let original = r##"    a
+      
+  
+    b
+
+
+"##;
+    let expected = r##"<pre><code>a
+  
+
+b
+</code></pre>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_110() {
+    let original = r##"    a
+
+
+paragraph
+"##;
+    let expected = r##"<pre><code>a
+</code></pre>
+<p>paragraph</p>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_111() {
+    let original = r##"```
+a
+```   
+b
+"##;
+    let expected = r##"<pre><code>a
+</code></pre>
+<p>b</p>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_112() {
+    let original = r##"```
+a
+``` foo
+b
+```
+"##;
+    let expected = r##"<pre><code>a
+``` foo
+b
+</code></pre>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_113() {
+    let original = r##"1. a
+2) b
+"##;
+    let expected = r##"<ol>
+<li>a</li>
+</ol>
+<ol start="2">
+<li>b</li>
+</ol>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_114() {
+    let original = r##"1) a
+2) b
+3. c
+"##;
+    let expected = r##"<ol>
+<li>a</li>
+<li>b</li>
+</ol>
+<ol start="3">
+<li>c</li>
+</ol>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_115() {
+    let original = r##"- a
++ b
++ c
+* d
+"##;
+    let expected = r##"<ul>
+<li>a</li>
+</ul>
+<ul>
+<li>b</li>
+<li>c</li>
+</ul>
+<ul>
+<li>d</li>
+</ul>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_116() {
+    let original = r##"- a
+
++ b
+"##;
+    let expected = r##"<ul>
+<li>a</li>
+</ul>
+<ul>
+<li>b</li>
+</ul>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_117() {
+    let original = r##"foo\
+
+bar
+"##;
+    let expected = r##"<p>foo\</p>
+<p>bar</p>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_118() {
+    let original = r##"foo\
+- bar
+"##;
+    let expected = r##"<p>foo\</p>
+<ul>
+<li>bar</li>
+</ul>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_119() {
+    let original = r##"> foo\
+bar\
+"##;
+    let expected = r##"<blockquote>
+<p>foo<br />
+bar\</p>
+</blockquote>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_120() {
+    let original = r##"*`a`*
+"##;
+    let expected = r##"<p><em><code>a</code></em></p>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_121() {
+    let original = r##"**`a`**
+"##;
+    let expected = r##"<p><strong><code>a</code></strong></p>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_122() {
+    let original = r##"a`b`*c*
+"##;
+    let expected = r##"<p>a<code>b</code><em>c</em></p>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_123() {
+    let original = r##"*a`*`*
+"##;
+    let expected = r##"<p><em>a<code>*</code></em></p>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_124() {
+    let original = r##"**`a`**b
+"##;
+    let expected = r##"<p>**<code>a</code>**b</p>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_125() {
+    let original = r##"- foo
+bar
+"##;
+    let expected = r##"<ul>
+<li>foo
+bar</li>
+</ul>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_126() {
+    let original = r##"- foo
+
+bar
+"##;
+    let expected = r##"<ul>
+<li>foo</li>
+</ul>
+<p>bar</p>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_127() {
+    let original = r##"1. a
+   - b
+c
+"##;
+    let expected = r##"<ol>
+<li>a
+<ul>
+<li>b
+c</li>
+</ul>
+</li>
+</ol>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_128() {
+    let original = r##"Foo
+- bar
+"##;
+    let expected = r##"<p>Foo</p>
+<ul>
+<li>bar</li>
+</ul>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_129() {
+    let original = r##"Foo
+---
+"##;
+    let expected = r##"<h2>Foo</h2>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_130() {
+    let original = r##"Foo
+- - -
+"##;
+    let expected = r##"<p>Foo</p>
+<hr />
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_131() {
+    let original = r##"- Foo
+---
+"##;
+    let expected = r##"<ul>
+<li>Foo</li>
+</ul>
+<hr />
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_132() {
+    let original = r##"- Foo
+  ---
+"##;
+    let expected = r##"<ul>
+<li>
+<h2>Foo</h2>
+</li>
+</ul>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_133() {
+    let original = r##"text
+```
+code
+```
+"##;
+    let expected = r##"<p>text</p>
+<pre><code>code
+</code></pre>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_134() {
+    let original = r##"text
+   ~~~ rust
+code
+~~~
+"##;
+    let expected = r##"<p>text</p>
+<pre><code class="language-rust">code
+</code></pre>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_135() {
+    let original = r##"text
+    ```
+code
+"##;
+    let expected = r##"<p>text
+```
+code</p>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_136() {
+    let original = r##"a </a> b
+"##;
+    let expected = r##"<p>a </a> b</p>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_137() {
+    let original = r##"a </a > b
+"##;
+    let expected = r##"<p>a </a > b</p>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_138() {
+    let original = r##"a </a x> b
+"##;
+    let expected = r##"<p>a &lt;/a x&gt; b</p>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_139() {
+    let original = r##"a < b
+"##;
+    let expected = r##"<p>a &lt; b</p>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_140() {
+    let original = r##"<3
+"##;
+    let expected = r##"<p>&lt;3</p>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_141() {
+    let original = r##"<not valid!> <1 not> <not a="tag>
+"##;
+    let expected = r##"<p>&lt;not valid!&gt; &lt;1 not&gt; &lt;not a=&quot;tag&gt;</p>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_142() {
+    let original = r##"a <not valid> b
+"##;
+    let expected = r##"<p>a <not valid> b</p>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_143() {
+    let original = r##"-
+- x
+"##;
+    let expected = r##"<ul>
+<li></li>
+<li>x</li>
+</ul>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_144() {
+    let original = r##"- - -
+"##;
+    let expected = r##"<hr />
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_145() {
+    let original = r##"-  -  -
+"##;
+    let expected = r##"<hr />
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_146() {
+    let original = r##"a <a
+href="x"> b
+"##;
+    let expected = r##"<p>a <a
+href="x"> b</p>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_147() {
+    let original = r##"a <a
+href="x b
+"##;
+    let expected = r##"<p>a &lt;a
+href=&quot;x b</p>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_148() {
+    let original = r##"a <a
+> b
+"##;
+    let expected = r##"<p>a &lt;a</p>
+<blockquote>
+<p>b</p>
+</blockquote>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_149() {
+    let original = r##"*Foo*
+===
+"##;
+    let expected = r##"<h1><em>Foo</em></h1>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_150() {
+    let original = r##"\# Foo
+---
+"##;
+    let expected = r##"<h2># Foo</h2>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_151() {
+    let original = r##"a `b`\
+[c](d)
+---
+"##;
+    let expected = r##"<h2>a <code>b</code><br />
+<a href="d">c</a></h2>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_152() {
+    let original = r##"***foo** bar*
+"##;
+    let expected = r##"<p><em><strong>foo</strong> bar</em></p>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_153() {
+    let original = r##"***foo* bar**
+"##;
+    let expected = r##"<p><strong><em>foo</em> bar</strong></p>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_154() {
+    let original = r##"*****foo*****
+"##;
+    let expected = r##"<p><em><strong><strong>foo</strong></strong></em></p>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_155() {
+    let original = r##"**foo*bar***
+"##;
+    let expected = r##"<p><strong>foo<em>bar</em></strong></p>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_156() {
+    let original = r##"foo
+<em>x</em>
+"##;
+    let expected = r##"<p>foo
+<em>x</em></p>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_157() {
+    let original = r##"foo
+<em>
+bar
+"##;
+    let expected = r##"<p>foo
+<em>
+bar</p>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_158() {
+    let original = r##"<em>
+bar
+"##;
+    let expected = r##"<em>
+bar
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_159() {
+    let original = r##"foo
+<div>x</div>
+"##;
+    let expected = r##"<p>foo</p>
+<div>x</div>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_160() {
+    let original = r##"- a
+
+  - b
+"##;
+    let expected = r##"<ul>
+<li>
+<p>a</p>
+<ul>
+<li>b</li>
+</ul>
+</li>
+</ul>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_161() {
+    let original = r##"- a
+  - b
+
+    c
+- d
+"##;
+    let expected = r##"<ul>
+<li>a
+<ul>
+<li>
+<p>b</p>
+<p>c</p>
+</li>
+</ul>
+</li>
+<li>d</li>
+</ul>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_162() {
+    let original = r##"- a
+  - b
+
+- c
+"##;
+    let expected = r##"<ul>
+<li>
+<p>a</p>
+<ul>
+<li>b</li>
+</ul>
+</li>
+<li>
+<p>c</p>
+</li>
+</ul>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_163() {
+    let original = r##"- a
+  - b
+  - c
+
+  d
+"##;
+    let expected = r##"<ul>
+<li>
+<p>a</p>
+<ul>
+<li>b</li>
+<li>c</li>
+</ul>
+<p>d</p>
+</li>
+</ul>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_164() {
+    let original = r##"x <a title="a&b=c<d"> y
+"##;
+    let expected = r##"<p>x <a title="a&b=c<d"> y</p>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_165() {
+    let original = r##"x <a title='a&amp;b=c<d>'> y
+"##;
+    let expected = r##"<p>x <a title='a&amp;b=c<d>'> y</p>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_166() {
+    let original = r##"x <a title=a&b> y
+"##;
+    let expected = r##"<p>x <a title=a&b> y</p>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_167() {
+    let original = r##"> > text
+continuation
+"##;
+    let expected = r##"<blockquote>
+<blockquote>
+<p>text
+continuation</p>
+</blockquote>
+</blockquote>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_168() {
+    let original = r##"> - > a
+b
+"##;
+    let expected = r##"<blockquote>
+<ul>
+<li>
+<blockquote>
+<p>a
+b</p>
+</blockquote>
+</li>
+</ul>
+</blockquote>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_169() {
+    let original = r##"> > a
+>
+b
+"##;
+    let expected = r##"<blockquote>
+<blockquote>
+<p>a</p>
+</blockquote>
+</blockquote>
+<p>b</p>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_170() {
+    let original = r##"> > a
+> - b
+"##;
+    let expected = r##"<blockquote>
+<blockquote>
+<p>a</p>
+</blockquote>
+<ul>
+<li>b</li>
+</ul>
+</blockquote>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_171() {
+    let original = r##"> - a
+
+b
+2. c
+"##;
+    let expected = r##"<blockquote>
+<ul>
+<li>a</li>
+</ul>
+</blockquote>
+<p>b
+2. c</p>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_172() {
+    let original = r##"    code
+---
+"##;
+    let expected = r##"<pre><code>code
+</code></pre>
+<hr />
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_173() {
+    let original = r##"    code
+===
+"##;
+    let expected = r##"<pre><code>code
+</code></pre>
+<p>===</p>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_174() {
+    let original = r##"```
+code
+```
+---
+"##;
+    let expected = r##"<pre><code>code
+</code></pre>
+<hr />
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_175() {
+    let original = r##"> quote
+---
+"##;
+    let expected = r##"<blockquote>
+<p>quote</p>
+</blockquote>
+<hr />
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_176() {
+    let original = r##"> quote
+===
+"##;
+    let expected = r##"<blockquote>
+<p>quote
+===</p>
+</blockquote>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_177() {
+    let original = r##"- item
+---
+"##;
+    let expected = r##"<ul>
+<li>item</li>
+</ul>
+<hr />
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_178() {
+    let original = r##"*foo `bar* baz`
+"##;
+    let expected = r##"<p>*foo <code>bar* baz</code></p>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_179() {
+    let original = r##"**foo `bar** baz`
+"##;
+    let expected = r##"<p>**foo <code>bar** baz</code></p>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_180() {
+    let original = r##"`foo*` bar*
+"##;
+    let expected = r##"<p><code>foo*</code> bar*</p>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_181() {
+    let original = r##"*a `b*` c* d*
+"##;
+    let expected = r##"<p><em>a <code>b*</code> c</em> d*</p>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_182() {
+    let original = r##"*foo `` ` * `` bar*
+"##;
+    let expected = r##"<p><em>foo <code>` *</code> bar</em></p>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_183() {
+    let original = r##"*foo `bar* baz
+"##;
+    let expected = r##"<p><em>foo `bar</em> baz</p>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_184() {
+    let original = r##"~~a `~~` b~~
+"##;
+    let expected = r##"<p><del>a <code>~~</code> b</del></p>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_185() {
+    let original = r##">	foo
+"##;
+    let expected = r##"<blockquote>
+<p>foo</p>
+</blockquote>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_186() {
+    let original = r##">	  foo
+"##;
+    let expected = r##"<blockquote>
+<pre><code>foo
+</code></pre>
+</blockquote>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_187() {
+    let original = r##" >		foo
+"##;
+    let expected = r##"<blockquote>
+<pre><code> foo
+</code></pre>
+</blockquote>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_188() {
+    let original = r##">  	foo
+"##;
+    let expected = r##"<blockquote>
+<p>foo</p>
+</blockquote>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_189() {
+    let original = r##">>		foo
+"##;
+    let expected = r##"<blockquote>
+<blockquote>
+<pre><code> foo
+</code></pre>
+</blockquote>
+</blockquote>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_190() {
+    let original = r##">	>		foo
+"##;
+    let expected = r##"<blockquote>
+<blockquote>
+<pre><code>  foo
+</code></pre>
+</blockquote>
+</blockquote>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_191() {
+    let original = r##"> -		foo
+"##;
+    let expected = r##"<blockquote>
+<ul>
+<li>
+<pre><code>foo
+</code></pre>
+</li>
+</ul>
+</blockquote>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_192() {
+    let original = r##">	```
+>		code
+>	```
+"##;
+    let expected = r##"<blockquote>
+<pre><code>	code
+</code></pre>
+</blockquote>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_193() {
+    let original = r##"    foo\
+    bar
+"##;
+    let expected = r##"<pre><code>foo\
+bar
+</code></pre>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_194() {
+    let original = r##"    foo  
+    bar
+"##;
+    let expected = r##"<pre><code>foo  
+bar
+</code></pre>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_195() {
+    let original = r##"```
+foo\
+bar  
+baz
+```
+"##;
+    let expected = r##"<pre><code>foo\
+bar  
+baz
+</code></pre>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_196() {
+    let original = r##"~~~
+foo\
+~~~
+"##;
+    let expected = r##"<pre><code>foo\
+</code></pre>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_197() {
+    let original = r##"> ```
+> foo\
+> bar  
+> ```
+"##;
+    let expected = r##"<blockquote>
+<pre><code>foo\
+bar  
+</code></pre>
+</blockquote>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_198() {
+    let original = r##"foo\
+    bar
+"##;
+    let expected = r##"<p>foo<br />
+bar</p>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_199() {
+    let original = r##"foo  
+    bar
+"##;
+    let expected = r##"<p>foo<br />
+bar</p>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_200() {
+    let original = r##"foo\
+	bar
+"##;
+    let expected = r##"<p>foo<br />
+bar</p>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_201() {
+    let original = r##"foo
+        bar
+"##;
+    let expected = r##"<p>foo
+bar</p>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_202() {
+    let original = r##"- foo\
+      bar
+"##;
+    let expected = r##"<ul>
+<li>foo<br />
+bar</li>
+</ul>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_203() {
+    let original = r##"> foo  
+>     bar
+"##;
+    let expected = r##"<blockquote>
+<p>foo<br />
+bar</p>
+</blockquote>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_204() {
+    let original = r##"-	foo
+
+	bar
+"##;
+    let expected = r##"<ul>
+<li>
+<p>foo</p>
+<p>bar</p>
+</li>
+</ul>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_205() {
+    let original = r##"10.	foo
+
+	bar
+"##;
+    let expected = r##"<ol start="10">
+<li>
+<p>foo</p>
+<p>bar</p>
+</li>
+</ol>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_206() {
+    let original = r##"-	foo
+
+		bar
+"##;
+    let expected = r##"<ul>
+<li>
+<p>foo</p>
+<pre><code>bar
+</code></pre>
+</li>
+</ul>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_207() {
+    let original = r##"- foo
+
+	  bar
+"##;
+    let expected = r##"<ul>
+<li>
+<p>foo</p>
+<pre><code>bar
+</code></pre>
+</li>
+</ul>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_208() {
+    let original = r##"- a
+	- b
+		- c
+"##;
+    let expected = r##"<ul>
+<li>a
+<ul>
+<li>b
+<ul>
+<li>c</li>
+</ul>
+</li>
+</ul>
+</li>
+</ul>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_209() {
+    let original = r##"*	foo
+
+	*	bar
+
+		baz
+"##;
+    let expected = r##"<ul>
+<li>
+<p>foo</p>
+<ul>
+<li>
+<p>bar</p>
+<p>baz</p>
+</li>
+</ul>
+</li>
+</ul>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_210() {
+    let original = r##"1.	- foo
+
+	  bar
+"##;
+    let expected = r##"<ol>
+<li>
+<ul>
+<li>
+<p>foo</p>
+<p>bar</p>
+</li>
+</ul>
+</li>
+</ol>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_211() {
+    let original = r##">	-	foo
+>
+>		bar
+"##;
+    let expected = r##"<blockquote>
+<ul>
+<li>
+<p>foo</p>
+<p>bar</p>
+</li>
+</ul>
+</blockquote>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_212() {
+    let original = r##"<http://example.com
+"##;
+    let expected = r##"<p>&lt;http://example.com</p>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_213() {
+    let original = r##"a <http://example.com *b* `c` [d](/e)
+"##;
+    let expected = r##"<p>a &lt;http://example.com <em>b</em> <code>c</code> <a href="/e">d</a></p>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_214() {
+    let original = r##"<foo@bar.com **b**
+"##;
+    let expected = r##"<p>&lt;foo@bar.com <strong>b</strong></p>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_215() {
+    let original = r##"<http://a.b *c*
+d
+"##;
+    let expected = r##"<p>&lt;http://a.b <em>c</em>
+d</p>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_216() {
+    let original = r##"<http://a.b
+>
+"##;
+    let expected = r##"<p>&lt;http://a.b
+&gt;</p>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_217() {
+    let original = r##"<http://a b> *c*
+"##;
+    let expected = r##"<p>&lt;http://a b&gt; <em>c</em></p>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn regression_test_218() {
+    let original = r##"<http://a.b <http://c.d>
+"##;
+    let expected = r##"<p>&lt;http://a.b <a href="http://c.d">http://c.d</a></p>
+"##;
+
+    test_markdown_html(original, expected);
+}
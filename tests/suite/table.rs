@@ -203,3 +203,33 @@ fn table_test_12() {
 
     test_markdown_html(original, expected);
 }
+
+#[test]
+fn table_test_13() {
+    let original = r##"|a|b|
+|-|-|
+|1|2|3|
+|4|
+"##;
+    let expected = r##"<table><thead><tr><th>a</th><th>b</th></tr></thead>
+<tr><td>1</td><td>2</td></tr>
+<tr><td>4</td><td></td></tr>
+</table>
+"##;
+
+    test_markdown_html(original, expected);
+}
+
+#[test]
+fn table_test_14() {
+    let original = r##"|a|b|c|
+|-|-|
+|x|y|z|
+"##;
+    let expected = r##"<p>|a|b|c|
+|-|-|
+|x|y|z|</p>
+"##;
+
+    test_markdown_html(original, expected);
+}